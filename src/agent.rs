@@ -0,0 +1,165 @@
+/* ---------- AVAILABLE AGENTS ---------- */
+pub mod perfect;
+pub mod imperfect;
+pub mod random;
+pub mod alpha_beta;
+pub mod mcts;
+pub mod external;
+
+/* ---------- UTILITIES ---------- */
+pub mod rng;
+
+
+use crate::game::{Game, Outcome};
+use uuid::Uuid;
+
+
+// Something which can play games, such as the solver, a search algorithm or
+// another program. Agents are told about every move made in a match, their
+// own included, right before it is played. Agents which cannot come up with
+// a legal move, such as programs which crash, return why instead.
+pub trait Agent {
+    fn name(&self) -> String;
+    fn choose_move(&mut self, game: &mut dyn Game) -> Result<Uuid, String>;
+
+    // Called when a new match starts from the game's current position.
+    fn reset(&mut self) {}
+
+    fn observe(&mut self, _game: &dyn Game, _mv: Uuid) {}
+}
+
+
+pub const AGENT_SPECS: &str =
+"perfect          Always plays one of the best moves, according to the solver
+random           Plays any legal move
+imperfect:<t>    Favors better moves, where t is easy, medium, hard or a temperature
+alphabeta:<d>    Searches d moves ahead with alpha-beta pruning (4 by default)
+mcts:<n>         Monte Carlo tree search with n playouts per move (1000 by default)";
+
+
+// Creates an agent from a short description, such as "alphabeta:6". Agents
+// which make random choices are seeded with the given seed.
+pub fn from_spec(spec: &str, seed: u64) -> Result<Box<dyn Agent>, String> {
+    let (kind, setting) = match spec.split_once(':') {
+        Some((kind, setting)) => (kind, Some(setting)),
+        None => (spec, None)
+    };
+    let count = |default: i64| -> Result<i64, String> {
+        match setting {
+            Some(text) => match text.parse() {
+                Ok(n) if n >= 1 => Ok(n),
+                _ => Err(format!("Invalid setting in agent '{}'.", spec))
+            },
+            None => Ok(default)
+        }
+    };
+    match kind {
+        "perfect" => Ok(Box::new(perfect::Perfect::new())),
+        "random" => Ok(Box::new(random::Random::new(seed))),
+        "imperfect" => {
+            let level = setting.unwrap_or("medium");
+            let temperature = match imperfect::Difficulty::parse(level) {
+                Some(difficulty) => difficulty.temperature(),
+                None => match level.parse::<f64>() {
+                    Ok(t) if t > 0.0 => t,
+                    _ => return Err(format!("Invalid setting in agent '{}'.", spec))
+                }
+            };
+            Ok(Box::new(imperfect::Imperfect::new(temperature, seed)))
+        },
+        "alphabeta" => Ok(Box::new(alpha_beta::AlphaBeta::new(count(4)? as i32, seed))),
+        "mcts" => Ok(Box::new(mcts::Mcts::new(count(1000)? as usize, seed))),
+        _ => Err(format!("Unknown agent '{}'.", spec))
+    }
+}
+
+
+// Moves in the current position, ordered by how the game writes them down.
+// Games keep their moves in hash maps, which list them in a different order
+// every time the program runs, so agents which pick among moves at random (or
+// break ties by taking the first one) start from this order instead. That
+// way, the same seed always leads to the same moves.
+pub fn ordered_moves(game: &dyn Game) -> Vec<Uuid> {
+    let mut moves = game.possible_moves();
+    moves.sort_by_cached_key(|mv| game.encode_move(*mv));
+    moves
+}
+
+
+// Plays a match between two agents from the game's current position, with
+// the first agent to move. Returns the moves made along with the outcome of
+// the match for the first agent, and leaves the game as it found it. An agent
+// which fails to choose a move forfeits, losing the match right away, and the
+// reason it failed is returned as well.
+pub fn play_match(
+    game: &mut dyn Game,
+    first: &mut dyn Agent,
+    second: &mut dyn Agent
+) -> (Vec<Uuid>, Outcome, Option<String>) {
+    first.reset();
+    second.reset();
+    let mut moves = Vec::new();
+    let mut forfeit = None;
+    let out = loop {
+        if let Some(out) = game.outcome() {
+            break out
+        }
+        let choice = if moves.len() % 2 == 0 {
+            first.choose_move(game)
+        } else {
+            second.choose_move(game)
+        };
+        let mv = match choice {
+            Ok(mv) => mv,
+            Err(message) => {
+                forfeit = Some(message);
+                break Outcome::Loss(0)
+            }
+        };
+        first.observe(game, mv);
+        second.observe(game, mv);
+        game.play(mv);
+        moves.push(mv);
+    };
+    for _ in moves.iter() {
+        game.undo();
+    }
+    // Terminal outcomes and forfeits are given for the player to move at the end
+    let out = if moves.len() % 2 == 0 {
+        out
+    } else {
+        match out {
+            Outcome::Win(rem) => Outcome::Loss(rem),
+            Outcome::Loss(rem) => Outcome::Win(rem),
+            tie => tie
+        }
+    };
+    (moves, out, forfeit)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::tic_tac_toe;
+
+    // Plays a match on a new board, whose moves are listed in a different
+    // order than on any other board, and writes the moves down.
+    fn seeded_match(spec: &str, seed: u64) -> Vec<String> {
+        let mut game = tic_tac_toe::Session::new(3, 3, 3);
+        let mut first = from_spec(spec, seed).unwrap();
+        let mut second = from_spec("random", seed + 1).unwrap();
+        let (moves, _, forfeit) = play_match(&mut game, first.as_mut(), second.as_mut());
+        assert!(forfeit.is_none());
+        moves.into_iter().map(|mv| game.encode_move(mv)).collect()
+    }
+
+    #[test]
+    fn seeded_matches_repeat() {
+        for spec in ["random", "imperfect:easy", "alphabeta:2", "mcts:50"] {
+            for seed in 0..5 {
+                assert_eq!(seeded_match(spec, seed), seeded_match(spec, seed), "{}", spec);
+            }
+        }
+    }
+}
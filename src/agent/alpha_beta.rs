@@ -0,0 +1,85 @@
+use super::{ordered_moves, Agent};
+use super::rng::Rng;
+use crate::game::{Game, Outcome};
+use uuid::Uuid;
+
+
+// Scores above this are won, and scores below its negation are lost
+const WIN: i32 = 1_000_000;
+
+
+// Depth-limited negamax search with alpha-beta pruning. Positions at the
+// search horizon are scored as even, so the agent plays perfectly near the
+// end of a game and aimlessly (but safely, when it can) before that. Ties
+// between equally good moves are broken at random.
+pub struct AlphaBeta {
+    depth: i32,
+    rng: Rng
+}
+
+impl AlphaBeta {
+    pub fn new(depth: i32, seed: u64) -> Self {
+        if depth < 1 {
+            panic!("Search depth must be at least 1.");
+        }
+        AlphaBeta { depth, rng: Rng::new(seed) }
+    }
+}
+
+impl Agent for AlphaBeta {
+    fn name(&self) -> String {
+        format!("AlphaBeta({})", self.depth)
+    }
+
+    fn choose_move(&mut self, game: &mut dyn Game) -> Result<Uuid, String> {
+        let mut best = -WIN - 1;
+        let mut best_moves = Vec::new();
+        for mv in ordered_moves(game) {
+            game.play(mv);
+            // A window just below the best score finds every move tied with it
+            let score = -negamax(game, self.depth - 1, 1, -WIN - 1, -(best - 1));
+            game.undo();
+            if score > best {
+                best = score;
+                best_moves.clear();
+            }
+            if score == best {
+                best_moves.push(mv);
+            }
+        }
+        if best_moves.is_empty() {
+            return Err("No moves to choose from.".to_string())
+        }
+        Ok(self.rng.choose(&best_moves))
+    }
+}
+
+
+/* HELPER FUNCTIONS */
+
+// Scores the position for the player to move. Wins closer to the root score
+// higher, and losses further away from it score higher.
+fn negamax(game: &mut dyn Game, depth: i32, ply: i32, mut alpha: i32, beta: i32) -> i32 {
+    if let Some(out) = game.outcome() {
+        return match out {
+            Outcome::Win(_) => WIN - ply,
+            Outcome::Loss(_) => -WIN + ply,
+            Outcome::Tie(_) => 0
+        }
+    }
+    if depth == 0 {
+        return 0
+    }
+    let mut best = -WIN - 1;
+    for mv in game.possible_moves() {
+        game.play(mv);
+        let score = -negamax(game, depth - 1, ply + 1, -beta, -alpha);
+        game.undo();
+        best = best.max(score);
+        alpha = alpha.max(score);
+        if alpha >= beta {
+            break
+        }
+    }
+    best
+}
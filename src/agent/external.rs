@@ -0,0 +1,213 @@
+use super::Agent;
+use crate::game::Game;
+use crate::game::registry::Arguments;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+
+// How long an engine gets to answer each command by default
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(10);
+
+
+// An agent backed by another program speaking the engine protocol (see
+// engine.rs) over its standard input and output. Before each of its moves,
+// the program is sent the whole list of moves made so far in the match. Its
+// output is read on a separate thread, so that an engine which hangs can be
+// given up on instead of blocking the match forever.
+pub struct ExternalEngine {
+    name: String,
+    child: Child,
+    input: Option<ChildStdin>,
+    output: Receiver<String>,
+    timeout: Duration,
+    moves: Vec<String>
+}
+
+impl ExternalEngine {
+    // Starts the program (split on whitespace into a path and arguments) and
+    // selects the game on it, with every parameter spelled out.
+    pub fn new(command: &str, game_id: &str, arguments: &Arguments) -> Result<Self, String> {
+        let mut words = command.split_whitespace();
+        let program = words.next().ok_or("Empty engine command.")?;
+        let mut child = Command::new(program)
+            .args(words)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Could not start '{}': {}", command, e))?;
+        let input = child.stdin.take().ok_or("Could not talk to the engine.")?;
+        let stdout = child.stdout.take().ok_or("Could not talk to the engine.")?;
+        let (sender, output) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break
+                }
+            }
+        });
+        let mut engine = ExternalEngine {
+            name: command.to_string(),
+            child,
+            input: Some(input),
+            output,
+            timeout: RESPONSE_TIMEOUT,
+            moves: Vec::new()
+        };
+        if let Ok(name) = engine.send("name") {
+            if !name.is_empty() {
+                engine.name = name;
+            }
+        }
+        let mut arguments: Vec<_> = arguments.iter().collect();
+        arguments.sort();
+        let mut select = format!("game {}", game_id);
+        for (name, value) in arguments {
+            select.push_str(&format!(" {}={}", name, value));
+        }
+        engine.send(&select)?;
+        Ok(engine)
+    }
+
+    // Longest time to wait for the answer to each command from now on.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    // Sends a command and returns the body of a successful response. An
+    // engine which takes too long to answer is stopped, as whatever it says
+    // afterwards could be mistaken for the answer to another command.
+    pub fn send(&mut self, command: &str) -> Result<String, String> {
+        let input = self.input.as_mut().ok_or(format!("{} has been closed.", self.name))?;
+        writeln!(input, "{}", command)
+            .and_then(|_| input.flush())
+            .map_err(|e| format!("Could not write to {}: {}", self.name, e))?;
+        let deadline = Instant::now() + self.timeout;
+        let mut lines = Vec::new();
+        loop {
+            let wait = deadline.saturating_duration_since(Instant::now());
+            let line = match self.output.recv_timeout(wait) {
+                Ok(line) => line.trim_end().to_string(),
+                Err(RecvTimeoutError::Timeout) => {
+                    self.stop();
+                    return Err(format!(
+                        "{} did not answer '{}' within {:.1} seconds.",
+                        self.name, command, self.timeout.as_secs_f64()
+                    ))
+                },
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(format!("{} stopped responding.", self.name))
+                }
+            };
+            if line.is_empty() {
+                if lines.is_empty() {
+                    continue
+                }
+                break
+            }
+            lines.push(line);
+        }
+        let response = lines.join("\n");
+        if let Some(body) = response.strip_prefix('=') {
+            Ok(body.trim().to_string())
+        } else if let Some(message) = response.strip_prefix('?') {
+            Err(format!("{} answered '{}': {}", self.name, command, message.trim()))
+        } else {
+            Err(format!("{} gave a malformed response: {}", self.name, response))
+        }
+    }
+
+    // Closing its input is enough for a well-behaved engine to stop, and the
+    // process is killed in case it did not, so that waiting never hangs.
+    fn stop(&mut self) {
+        drop(self.input.take());
+        self.child.kill().ok();
+        self.child.wait().ok();
+    }
+}
+
+impl Agent for ExternalEngine {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn reset(&mut self) {
+        self.moves.clear();
+    }
+
+    fn observe(&mut self, game: &dyn Game, mv: Uuid) {
+        self.moves.push(game.encode_move(mv));
+    }
+
+    fn choose_move(&mut self, game: &mut dyn Game) -> Result<Uuid, String> {
+        let position = format!("position {}", self.moves.join(" "));
+        self.send(position.trim_end())?;
+        let text = self.send("bestmove")?;
+        game.decode_move(&text)
+            .ok_or(format!("{} played an illegal move: '{}'.", self.name, text))
+    }
+}
+
+impl Drop for ExternalEngine {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::agent::{play_match, perfect::Perfect};
+    use crate::game::{tic_tac_toe, Outcome};
+    use std::fs;
+
+    // A shell script standing in for an engine, which runs the given command
+    // to answer bestmove, and answers every other command with an empty
+    // success.
+    fn fake_engine(name: &str, bestmove: &str) -> ExternalEngine {
+        let path = std::env::temp_dir().join(format!("game_tree_{}_{}.sh", name, std::process::id()));
+        let script = format!(
+            "while read command; do\n  case $command in\n    bestmove) {} ;;\n    *) printf '=\\n\\n' ;;\n  esac\ndone\n",
+            bestmove
+        );
+        fs::write(&path, script).unwrap();
+        let arguments = Arguments::from([
+            ("height".to_string(), 3),
+            ("width".to_string(), 3),
+            ("win".to_string(), 3)
+        ]);
+        let engine = ExternalEngine::new(&format!("sh {}", path.display()), "tic-tac-toe", &arguments);
+        fs::remove_file(&path).ok();
+        engine.unwrap()
+    }
+
+    // Always answers b2, which is legal until b2 has been taken.
+    #[test]
+    fn plays_the_moves_it_is_given() {
+        let mut engine = fake_engine("fixed", "printf '= b2\\n\\n'");
+        let mut game = tic_tac_toe::Session::new(3, 3, 3);
+        let (moves, out, forfeit) = play_match(&mut game, &mut engine, &mut Perfect::new());
+        assert_eq!(game.encode_move(moves[0]), "b2");
+        assert_eq!(moves.len(), 2);
+        assert!(out == Outcome::Loss(0));
+        assert!(forfeit.unwrap().contains("illegal move: 'b2'"));
+    }
+
+    #[test]
+    fn forfeits_when_it_stops_answering() {
+        let mut engine = fake_engine("silent", "sleep 30");
+        engine.set_timeout(Duration::from_millis(200));
+        let mut game = tic_tac_toe::Session::new(3, 3, 3);
+        let started = Instant::now();
+        let (moves, out, forfeit) = play_match(&mut game, &mut engine, &mut Perfect::new());
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(moves.is_empty());
+        assert!(out == Outcome::Loss(0));
+        assert!(forfeit.unwrap().contains("did not answer 'bestmove'"));
+        assert!(engine.send("bestmove").is_err());
+    }
+}
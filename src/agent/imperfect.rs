@@ -0,0 +1,110 @@
+use super::{ordered_moves, Agent};
+use super::rng::Rng;
+use crate::game::{solve, move_values, Game, Outcome};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+
+// Preset strengths, from most to least forgiving.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard
+}
+
+impl Difficulty {
+    pub fn parse(text: &str) -> Option<Self> {
+        match text.to_lowercase().as_str() {
+            "easy" => Some(Difficulty::Easy),
+            "medium" => Some(Difficulty::Medium),
+            "hard" => Some(Difficulty::Hard),
+            _ => None
+        }
+    }
+
+    pub fn temperature(&self) -> f64 {
+        match self {
+            Difficulty::Easy => 1.0,
+            Difficulty::Medium => 0.4,
+            Difficulty::Hard => 0.15
+        }
+    }
+}
+
+
+// Picks moves at random, favoring the better ones according to the solver.
+// Every move gets a score (about 2 for a win, 1 for a tie and 0 for a loss,
+// nudged up for quick wins and down for quick losses), and is picked with
+// probability proportional to exp(score / temperature). Low temperatures
+// play almost perfectly, and high ones play almost at random.
+pub struct Imperfect {
+    temperature: f64,
+    seen: HashMap<i32, Outcome>,
+    rng: Rng
+}
+
+impl Imperfect {
+    pub fn new(temperature: f64, seed: u64) -> Self {
+        if temperature <= 0.0 {
+            panic!("Temperature must be positive.");
+        }
+        Imperfect {
+            temperature,
+            seen: HashMap::new(),
+            rng: Rng::new(seed)
+        }
+    }
+
+    // Starts off with an already solved table.
+    pub fn with_table(temperature: f64, seed: u64, seen: HashMap<i32, Outcome>) -> Self {
+        let mut agent = Imperfect::new(temperature, seed);
+        agent.seen = seen;
+        agent
+    }
+}
+
+impl Agent for Imperfect {
+    fn name(&self) -> String {
+        format!("Imperfect({})", self.temperature)
+    }
+
+    fn choose_move(&mut self, game: &mut dyn Game) -> Result<Uuid, String> {
+        if !self.seen.contains_key(&game.state()) {
+            let out = solve(game, &mut self.seen);
+            self.seen.insert(game.state(), out);
+        }
+        let mut values = move_values(game, &self.seen);
+        if values.is_empty() {
+            return Err("No moves to choose from.".to_string())
+        }
+        // Scores are shifted by the best one to keep the exponentials finite
+        let best = score(values[0].1);
+        // Sampled from a fixed order, so that the same seed picks the same move
+        let order = ordered_moves(game);
+        values.sort_by_key(|(mv, _)| order.iter().position(|other| other == mv));
+        let weights: Vec<f64> = values
+            .iter()
+            .map(|(_, out)| ((score(*out) - best) / self.temperature).exp())
+            .collect();
+        let mut target = self.rng.next_f64() * weights.iter().sum::<f64>();
+        for ((mv, _), weight) in values.iter().zip(weights) {
+            if target < weight {
+                return Ok(*mv)
+            }
+            target -= weight;
+        }
+        Ok(values[values.len() - 1].0)
+    }
+}
+
+
+/* HELPER FUNCTIONS */
+
+fn score(out: Outcome) -> f64 {
+    match out {
+        Outcome::Win(rem) => 2.0 + 0.5 / (rem as f64 + 1.0),
+        Outcome::Tie(_) => 1.0,
+        Outcome::Loss(rem) => 0.5 - 0.5 / (rem as f64 + 1.0)
+    }
+}
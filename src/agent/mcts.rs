@@ -0,0 +1,119 @@
+use super::{ordered_moves, Agent};
+use super::rng::Rng;
+use crate::game::{Game, Outcome};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+
+// Exploration constant for UCT, the usual square root of two
+const EXPLORATION: f64 = std::f64::consts::SQRT_2;
+
+
+// Monte Carlo tree search with UCT selection and random playouts. Statistics
+// are kept by state, so positions reached in different ways share them. A
+// new tree is grown for every move.
+pub struct Mcts {
+    iterations: usize,
+    rng: Rng
+}
+
+// Number of visits, and total reward for the player who moved into the state.
+#[derive(Default, Clone, Copy)]
+struct Stats {
+    visits: f64,
+    reward: f64
+}
+
+impl Mcts {
+    pub fn new(iterations: usize, seed: u64) -> Self {
+        if iterations == 0 {
+            panic!("MCTS needs at least one iteration.");
+        }
+        Mcts { iterations, rng: Rng::new(seed) }
+    }
+
+    // Walks down the tree and back, playing one random game on the way.
+    fn iterate(&mut self, game: &mut dyn Game, tree: &mut HashMap<i32, Stats>) {
+        let mut path = vec![game.state()];
+        let mut plies = 0;
+        // Selection and expansion
+        while game.outcome().is_none() {
+            let moves = ordered_moves(game);
+            let parent = tree.get(&game.state()).copied().unwrap_or_default();
+            let mut unvisited = Vec::new();
+            let mut best = (f64::MIN, moves[0]);
+            for mv in moves.iter() {
+                game.play(*mv);
+                match tree.get(&game.state()) {
+                    Some(child) if child.visits > 0.0 => {
+                        let score = child.reward / child.visits
+                            + EXPLORATION * (parent.visits.ln() / child.visits).sqrt();
+                        if score > best.0 {
+                            best = (score, *mv);
+                        }
+                    },
+                    _ => unvisited.push(*mv)
+                }
+                game.undo();
+            }
+            let expanding = !unvisited.is_empty();
+            let mv = if expanding { self.rng.choose(&unvisited) } else { best.1 };
+            game.play(mv);
+            plies += 1;
+            path.push(game.state());
+            if expanding {
+                break
+            }
+        }
+        // Random playout
+        let mut depth = plies;
+        while game.outcome().is_none() {
+            let moves = ordered_moves(game);
+            game.play(self.rng.choose(&moves));
+            depth += 1;
+        }
+        let reward = match game.outcome().unwrap() {
+            Outcome::Win(_) => 1.0,
+            Outcome::Tie(_) => 0.5,
+            Outcome::Loss(_) => 0.0
+        };
+        for _ in 0..depth {
+            game.undo();
+        }
+        // Backpropagation, where the state at index k was moved into by the
+        // player to move at index k - 1
+        for (k, encoded_state) in path.iter().enumerate() {
+            let stats = tree.entry(*encoded_state).or_default();
+            stats.visits += 1.0;
+            if k > 0 && (depth - (k - 1)) % 2 == 0 {
+                stats.reward += reward;
+            } else if k > 0 {
+                stats.reward += 1.0 - reward;
+            }
+        }
+    }
+}
+
+impl Agent for Mcts {
+    fn name(&self) -> String {
+        format!("MCTS({})", self.iterations)
+    }
+
+    fn choose_move(&mut self, game: &mut dyn Game) -> Result<Uuid, String> {
+        let mut tree: HashMap<i32, Stats> = HashMap::new();
+        for _ in 0..self.iterations {
+            self.iterate(game, &mut tree);
+        }
+        // The most visited move is the most robust choice
+        let mut best: Option<(f64, Uuid)> = None;
+        for mv in ordered_moves(game) {
+            game.play(mv);
+            let visits = tree.get(&game.state()).map(|s| s.visits).unwrap_or(0.0);
+            game.undo();
+            if best.is_none_or(|(most, _)| visits > most) {
+                best = Some((visits, mv));
+            }
+        }
+        best.map(|(_, mv)| mv).ok_or("No moves to choose from.".to_string())
+    }
+}
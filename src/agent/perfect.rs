@@ -0,0 +1,48 @@
+use super::{ordered_moves, Agent};
+use crate::game::{solve, move_values, Game, Outcome};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+
+// Plays one of the best moves in every position, according to the solver.
+// Positions are solved the first time they are seen.
+pub struct Perfect {
+    seen: HashMap<i32, Outcome>
+}
+
+impl Default for Perfect {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Perfect {
+    pub fn new() -> Self {
+        Perfect { seen: HashMap::new() }
+    }
+
+    // Starts off with an already solved table.
+    pub fn with_table(seen: HashMap<i32, Outcome>) -> Self {
+        Perfect { seen }
+    }
+}
+
+impl Agent for Perfect {
+    fn name(&self) -> String {
+        "Perfect".to_string()
+    }
+
+    fn choose_move(&mut self, game: &mut dyn Game) -> Result<Uuid, String> {
+        if !self.seen.contains_key(&game.state()) {
+            let out = solve(game, &mut self.seen);
+            self.seen.insert(game.state(), out);
+        }
+        // The first of the best moves, in a fixed order
+        let values = move_values(game, &self.seen);
+        let (_, best) = values.first().ok_or("No moves to choose from.".to_string())?;
+        ordered_moves(game)
+            .into_iter()
+            .find(|mv| values.iter().any(|(other, out)| other == mv && out == best))
+            .ok_or("No moves to choose from.".to_string())
+    }
+}
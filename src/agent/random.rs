@@ -0,0 +1,30 @@
+use super::{ordered_moves, Agent};
+use super::rng::Rng;
+use crate::game::Game;
+use uuid::Uuid;
+
+
+// Plays any legal move, uniformly at random.
+pub struct Random {
+    rng: Rng
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        Random { rng: Rng::new(seed) }
+    }
+}
+
+impl Agent for Random {
+    fn name(&self) -> String {
+        "Random".to_string()
+    }
+
+    fn choose_move(&mut self, game: &mut dyn Game) -> Result<Uuid, String> {
+        let moves = ordered_moves(game);
+        if moves.is_empty() {
+            return Err("No moves to choose from.".to_string())
+        }
+        Ok(self.rng.choose(&moves))
+    }
+}
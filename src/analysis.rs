@@ -0,0 +1,302 @@
+use std::collections::HashMap;
+use crate::game::{move_values, Game, Outcome};
+use crate::ui::describe;
+use uuid::Uuid;
+
+
+// A move which led to a worse kind of outcome (a tie or a loss instead of a
+// win, or a loss instead of a tie) than the best move available.
+pub struct Mistake {
+    pub ply: usize,
+    pub notation: String,
+    pub best: Outcome,
+    pub played: Outcome
+}
+
+
+// How a move compares to the best one available. Inaccuracies keep the same
+// kind of outcome but win more slowly (or tie later, or lose sooner), while
+// blunders throw away a win or a tie.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Verdict {
+    Best,
+    Inaccuracy,
+    Blunder
+}
+
+impl Verdict {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Verdict::Best => "best",
+            Verdict::Inaccuracy => "inaccuracy",
+            Verdict::Blunder => "blunder"
+        }
+    }
+}
+
+
+// A move of a match along with its verdict. The position's value before the
+// move and the move's value (that is, the position's value after it) are
+// both from the perspective of the player who made the move. The best
+// alternative is only given for moves which were not among the best.
+pub struct Annotation {
+    pub ply: usize,
+    pub notation: String,
+    pub verdict: Verdict,
+    pub before: Outcome,
+    pub after: Outcome,
+    pub alternative: Option<String>
+}
+
+
+// Prints how many positions in the table are wins, losses, and ties, for
+// each remoteness. Works just as well on tables filled by puzzle solvers.
+pub fn analyze(state_map: &HashMap<i32, Outcome>) {
+    let mut map: HashMap<i32, (i32, i32, i32, i32)> = HashMap::new();
+    for out in state_map.values() {
+        match out {
+            Outcome::Loss(rem) => { 
+                let values = map.entry(*rem)
+                    .or_insert((0, 0, 0, 0));
+                values.0 += 1;
+                values.3 += 1;
+            },
+            Outcome::Win(rem) => { 
+                let values = map.entry(*rem)
+                    .or_insert((0, 0, 0, 0));
+                values.2 += 1;
+                values.3 += 1;
+            },
+            Outcome::Tie(rem) => { 
+                let values = map.entry(*rem)
+                    .or_insert((0, 0, 0, 0));
+                values.1 += 1;
+                values.3 += 1;
+            }
+        }
+    }
+    let mut totals = (0, 0, 0, 0);
+    let mut collected = Vec::new();
+    for (rem, values) in map.iter() {
+        collected.push((rem, values));
+    }
+    collected.sort_by(|a, b| b.0.cmp(a.0));
+    println!("Rem\tWin\tLose\tTie\tTotal");
+    println!("---------------------------------------");
+    for row in collected {
+        println!("{}\t{}\t{}\t{}\t{}\t", row.0, row.1.2, row.1.0, row.1.1, row.1.3);
+        totals.0 += row.1.0;
+        totals.1 += row.1.1;
+        totals.2 += row.1.2;
+        totals.3 += row.1.3;
+    }
+    println!("---------------------------------------");
+    println!("Tot\t{}\t{}\t{}\t{}\t\n", totals.2, totals.0, totals.1, totals.3);
+}
+
+
+// Replays the moves from the game's current position and returns the ones
+// which were mistakes, according to a table filled by solve. Outcomes are
+// from the perspective of the player who made the move.
+pub fn find_mistakes(
+    game: &mut dyn Game,
+    moves: &[Uuid],
+    seen: &HashMap<i32, Outcome>
+) -> Result<Vec<Mistake>, String> {
+    let mistakes = annotate(game, moves, seen)?
+        .into_iter()
+        .filter(|a| a.verdict == Verdict::Blunder)
+        .map(|a| Mistake {
+            ply: a.ply,
+            notation: a.notation,
+            best: a.before,
+            played: a.after
+        })
+        .collect();
+    Ok(mistakes)
+}
+
+
+// Replays the moves from the game's current position and grades every one
+// of them against the best move available, according to a table filled by
+// solve. Leaves the game as it found it, and fails if any of the moves is
+// not legal, such as a move made after the game is over.
+pub fn annotate(
+    game: &mut dyn Game,
+    moves: &[Uuid],
+    seen: &HashMap<i32, Outcome>
+) -> Result<Vec<Annotation>, String> {
+    let mut annotations = Vec::new();
+    for (ply, mv) in moves.iter().enumerate() {
+        let values = move_values(game, seen);
+        let played = values.iter().find(|(id, _)| id == mv);
+        let ((best_move, best), (_, played)) = match (values.first(), played) {
+            (Some(best), Some(played)) => (*best, *played),
+            _ => {
+                let message = match game.outcome() {
+                    Some(_) => format!("Move {} was made after the game was over.", ply + 1),
+                    None => format!("Move {} is not legal in its position.", ply + 1)
+                };
+                for _ in 0..ply {
+                    game.undo();
+                }
+                return Err(message)
+            }
+        };
+        let verdict = if rank(played) > rank(best) {
+            Verdict::Blunder
+        } else if played != best {
+            Verdict::Inaccuracy
+        } else {
+            Verdict::Best
+        };
+        annotations.push(Annotation {
+            ply,
+            notation: game.encode_move(*mv),
+            verdict,
+            before: best,
+            after: played,
+            alternative: (verdict != Verdict::Best).then(|| game.encode_move(best_move))
+        });
+        game.play(*mv);
+    }
+    for _ in moves.iter() {
+        game.undo();
+    }
+    Ok(annotations)
+}
+
+
+// Value of every position a session went through, from its first position
+// to its current one, each for the player to move in it. Works back from the
+// moves the session remembers, and leaves it as it found it.
+pub fn value_history(game: &mut dyn Game, seen: &HashMap<i32, Outcome>) -> Vec<Outcome> {
+    let history = game.history();
+    for _ in history.iter() {
+        game.undo();
+    }
+    let mut values = Vec::new();
+    for mv in history.iter() {
+        values.push(value(game, seen));
+        game.play(*mv);
+    }
+    values.push(value(game, seen));
+    values
+}
+
+
+// Prints one line per move, followed by how many moves of each kind the
+// first and second players made.
+pub fn print_review(annotations: &[Annotation]) {
+    println!("{:<6}{:<8}{:<12}{:<16}{:<16}Best", "Ply", "Move", "Verdict", "Before", "After");
+    println!("{}", "-".repeat(70));
+    for a in annotations {
+        let best = match &a.alternative {
+            Some(alternative) => format!("{} ({})", alternative, describe(a.before)),
+            None => String::new()
+        };
+        let line = format!(
+            "{:<6}{:<8}{:<12}{:<16}{:<16}{}",
+            a.ply + 1, a.notation, a.verdict.label(), describe(a.before), describe(a.after), best
+        );
+        println!("{}", line.trim_end());
+    }
+    println!("{}", "-".repeat(70));
+    for (side, name) in ["First player", "Second player"].iter().enumerate() {
+        let count = |verdict: Verdict| {
+            annotations
+                .iter()
+                .filter(|a| a.ply % 2 == side && a.verdict == verdict)
+                .count()
+        };
+        println!(
+            "{}: best {}, inaccuracies {}, blunders {}",
+            name, count(Verdict::Best), count(Verdict::Inaccuracy), count(Verdict::Blunder)
+        );
+    }
+}
+
+
+/* HELPER FUNCTIONS */
+
+fn value(game: &dyn Game, seen: &HashMap<i32, Outcome>) -> Outcome {
+    match game.outcome() {
+        Some(out) => out,
+        None => *seen.get(&game.state()).expect("Position has not been solved.")
+    }
+}
+
+
+fn rank(out: Outcome) -> i32 {
+    match out {
+        Outcome::Win(_) => 0,
+        Outcome::Tie(_) => 1,
+        Outcome::Loss(_) => 2
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{solve, zero_by_1_2, zero_by_1_3_4};
+
+    fn review(game: &mut dyn Game, moves: &[&str]) -> Result<Vec<Annotation>, String> {
+        let mut seen = HashMap::new();
+        solve(game, &mut seen);
+        let moves: Vec<Uuid> = moves.iter().map(|mv| game.decode_move(mv).unwrap()).collect();
+        let start = game.position();
+        let annotations = annotate(game, &moves, &seen);
+        assert_eq!(game.position(), start);
+        annotations
+    }
+
+    fn verdicts(annotations: &[Annotation]) -> Vec<Verdict> {
+        annotations.iter().map(|a| a.verdict).collect()
+    }
+
+    #[test]
+    fn finds_blunders() {
+        // Taking two from four leaves two, which the opponent takes to win
+        let mut game = zero_by_1_2::Session::new(4);
+        let annotations = review(&mut game, &["2", "2"]).unwrap();
+        assert_eq!(verdicts(&annotations), vec![Verdict::Blunder, Verdict::Best]);
+        let blunder = &annotations[0];
+        assert_eq!(blunder.notation, "2");
+        assert!(blunder.before == Outcome::Win(3));
+        assert!(blunder.after == Outcome::Loss(2));
+        assert_eq!(blunder.alternative.as_deref(), Some("1"));
+        assert!(annotations[1].before == Outcome::Win(1));
+        assert_eq!(annotations[1].alternative, None);
+
+        let mut seen = HashMap::new();
+        solve(&mut game, &mut seen);
+        let moves = vec![game.decode_move("2").unwrap(), game.decode_move("2").unwrap()];
+        let mistakes = find_mistakes(&mut game, &moves, &seen).unwrap();
+        assert_eq!(mistakes.len(), 1);
+        assert_eq!(mistakes[0].ply, 0);
+        assert!(mistakes[0].played == Outcome::Loss(2));
+    }
+
+    #[test]
+    fn finds_inaccuracies() {
+        // Every move from zero-by-1-2's losing positions loses just as slowly,
+        // so this takes a game with more choice. From ten coins taking one
+        // still wins, only later than taking three, and from the nine left
+        // taking four loses sooner than taking one.
+        let mut game = zero_by_1_3_4::Session::new(10);
+        let annotations = review(&mut game, &["1", "4"]).unwrap();
+        assert_eq!(verdicts(&annotations), vec![Verdict::Inaccuracy, Verdict::Inaccuracy]);
+        assert!(annotations[0].before == Outcome::Win(5) && annotations[0].after == Outcome::Win(7));
+        assert_eq!(annotations[0].alternative.as_deref(), Some("3"));
+        assert!(annotations[1].before == Outcome::Loss(6) && annotations[1].after == Outcome::Loss(4));
+        assert_eq!(annotations[1].alternative.as_deref(), Some("1"));
+    }
+
+    #[test]
+    fn rejects_moves_after_the_game_is_over() {
+        let mut game = zero_by_1_2::Session::new(2);
+        let error = review(&mut game, &["2", "1"]).err();
+        assert_eq!(error.as_deref(), Some("Move 2 was made after the game was over."));
+    }
+}
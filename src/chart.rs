@@ -0,0 +1,243 @@
+use crate::game::Outcome;
+use crate::ui::describe;
+
+
+// Size in pixels of one remoteness step across and of one ply down, and of
+// the space around the plot, in SVG charts
+const COLUMN: i32 = 24;
+const ROW: i32 = 24;
+const MARGIN: i32 = 56;
+
+// Colors for positions which are wins, losses and ties for the player to move
+const WIN_COLOR: &str = "#2e7d32";
+const LOSS_COLOR: &str = "#c62828";
+const TIE_COLOR: &str = "#f9a825";
+
+
+// Value history charts, as in the GamesCrafters tools. Every ply is a row,
+// from the first position at the top down to the last one. Positions the
+// first player is winning go on the left and ones the second player is
+// winning go on the right, with remoteness zero at the edges and growing
+// towards the middle, where the ties go. So the further a point is from the
+// middle, the sooner someone wins.
+//
+// Values are given for the player to move, as returned by value_history,
+// and moves are the notation of the moves between them.
+pub fn ascii(values: &[Outcome], moves: &[String]) -> String {
+    let max = largest_remoteness(values);
+    let width = column(Standing::Tie, max) * 2 + 1;
+    let axis: String = (0..width).map(|c| axis_label(c, max)).collect();
+    let mut lines = vec![
+        format!("{:<11}{:<w$}|{:>w$}", "", "< First", "Second >", w = width as usize / 2),
+        format!("{:<11}{}", "Ply  Move", axis)
+    ];
+    for (ply, out) in values.iter().enumerate() {
+        let mut row: Vec<char> = (0..width)
+            .map(|c| if c == column(Standing::Tie, max) { '|' } else { ' ' })
+            .collect();
+        row[column(standing(*out, ply), max) as usize] = match out {
+            Outcome::Win(_) => 'W',
+            Outcome::Loss(_) => 'L',
+            Outcome::Tie(_) => 'T'
+        };
+        let played = if ply == 0 { "" } else { moves[ply - 1].as_str() };
+        let row: String = row.into_iter().collect();
+        lines.push(format!("{:<5}{:<6}{}  {}", ply, played, row, describe(*out)));
+    }
+    lines.join("\n")
+}
+
+
+// The same chart as a standalone SVG image, with the value of each point
+// shown when hovering over it.
+pub fn svg(values: &[Outcome], moves: &[String]) -> String {
+    let max = largest_remoteness(values);
+    let columns = column(Standing::Tie, max) * 2;
+    let x = |c: i32| MARGIN + c * COLUMN;
+    let y = |ply: usize| MARGIN + ply as i32 * ROW;
+    let width = x(columns) + MARGIN;
+    let height = y(values.len().saturating_sub(1)) + MARGIN;
+    let mut parts = vec![
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" \
+             font-family=\"sans-serif\" font-size=\"11\">",
+            width, height
+        ),
+        format!("<rect width=\"{}\" height=\"{}\" fill=\"white\"/>", width, height),
+        format!("<text x=\"{}\" y=\"16\">First player wins</text>", x(0)),
+        format!(
+            "<text x=\"{}\" y=\"16\" text-anchor=\"end\">Second player wins</text>",
+            x(columns)
+        )
+    ];
+    for c in 0..=columns {
+        let middle = c == columns / 2;
+        parts.push(format!(
+            "<line x1=\"{0}\" y1=\"{1}\" x2=\"{0}\" y2=\"{2}\" stroke=\"{3}\"/>",
+            x(c), y(0) - 8, height - MARGIN + 8, if middle { "#888" } else { "#e0e0e0" }
+        ));
+        let label = if middle { "T".to_string() } else { remoteness_at(c, max).to_string() };
+        parts.push(format!(
+            "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\">{}</text>",
+            x(c), MARGIN - 16, label
+        ));
+    }
+    let points: Vec<String> = values
+        .iter()
+        .enumerate()
+        .map(|(ply, out)| format!("{},{}", x(column(standing(*out, ply), max)), y(ply)))
+        .collect();
+    parts.push(format!(
+        "<polyline points=\"{}\" fill=\"none\" stroke=\"#888\" stroke-width=\"2\"/>",
+        points.join(" ")
+    ));
+    for (ply, out) in values.iter().enumerate() {
+        let color = match out {
+            Outcome::Win(_) => WIN_COLOR,
+            Outcome::Loss(_) => LOSS_COLOR,
+            Outcome::Tie(_) => TIE_COLOR
+        };
+        let played = if ply == 0 { "start".to_string() } else { moves[ply - 1].clone() };
+        parts.push(format!(
+            "<text x=\"{}\" y=\"{}\" text-anchor=\"end\">{}</text>",
+            MARGIN - 16, y(ply) + 4, escape(&played)
+        ));
+        parts.push(format!(
+            "<circle cx=\"{}\" cy=\"{}\" r=\"6\" fill=\"{}\"><title>{}. {}: {} for the player to move</title></circle>",
+            x(column(standing(*out, ply), max)), y(ply), color, ply, escape(&played), describe(*out)
+        ));
+    }
+    parts.push("</svg>".to_string());
+    parts.join("\n") + "\n"
+}
+
+
+/* HELPER FUNCTIONS */
+
+// Which player is winning a position, and in how many moves.
+#[derive(Clone, Copy)]
+enum Standing {
+    First(i32),
+    Second(i32),
+    Tie
+}
+
+// Turns the value of a position for the player to move into a standing,
+// given how many moves led to it.
+fn standing(out: Outcome, ply: usize) -> Standing {
+    let first_to_move = ply.is_multiple_of(2);
+    match (out, first_to_move) {
+        (Outcome::Tie(_), _) => Standing::Tie,
+        (Outcome::Win(rem), true) | (Outcome::Loss(rem), false) => Standing::First(rem),
+        (Outcome::Win(rem), false) | (Outcome::Loss(rem), true) => Standing::Second(rem)
+    }
+}
+
+// Column of a standing, counting from the left edge of the chart.
+fn column(standing: Standing, max: i32) -> i32 {
+    match standing {
+        Standing::First(rem) => rem,
+        Standing::Tie => max + 1,
+        Standing::Second(rem) => 2 * max + 2 - rem
+    }
+}
+
+// Remoteness that a column (other than the middle one) stands for.
+fn remoteness_at(column: i32, max: i32) -> i32 {
+    if column <= max {
+        column
+    } else {
+        2 * max + 2 - column
+    }
+}
+
+// Last digit of each column's remoteness, with a bar for the middle.
+fn axis_label(column: i32, max: i32) -> char {
+    if column == max + 1 {
+        '|'
+    } else {
+        char::from_digit((remoteness_at(column, max) % 10) as u32, 10).unwrap_or('?')
+    }
+}
+
+fn largest_remoteness(values: &[Outcome]) -> i32 {
+    values
+        .iter()
+        .map(|out| match out {
+            Outcome::Win(rem) | Outcome::Loss(rem) | Outcome::Tie(rem) => *rem
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::value_history;
+    use crate::game::{solve, zero_by_1_2, Game};
+    use std::collections::HashMap;
+
+    // Four coins where the first player takes two, throwing away their win,
+    // and the second player takes the last two.
+    fn blunder_line() -> (Vec<Outcome>, Vec<String>) {
+        let mut game = zero_by_1_2::Session::new(4);
+        let mut seen = HashMap::new();
+        let out = solve(&mut game, &mut seen);
+        seen.insert(game.state(), out);
+        let moves = vec!["2".to_string(), "2".to_string()];
+        for mv in moves.iter() {
+            game.play(game.decode_move(mv).unwrap());
+        }
+        let position = game.position();
+        let values = value_history(&mut game, &seen);
+        assert_eq!(game.position(), position);
+        (values, moves)
+    }
+
+    #[test]
+    fn value_history_follows_the_line() {
+        let (values, _) = blunder_line();
+        assert!(values == vec![Outcome::Win(3), Outcome::Win(1), Outcome::Loss(0)]);
+    }
+
+    #[test]
+    fn ascii_places_each_ply_by_who_is_winning() {
+        let (values, moves) = blunder_line();
+        let expected = [
+            "           < First|Second >",
+            "Ply  Move  0123|3210",
+            "0             W|      win in 3",
+            "1    2         |  W   win in 1",
+            "2    2         |   L  loss in 0"
+        ];
+        assert_eq!(ascii(&values, &moves), expected.join("\n"));
+    }
+
+    #[test]
+    fn ties_go_in_the_middle() {
+        let values = vec![Outcome::Tie(2), Outcome::Tie(1), Outcome::Tie(0)];
+        let moves = vec!["a".to_string(), "b".to_string()];
+        for line in ascii(&values, &moves).lines().skip(2) {
+            assert_eq!(line.find('T'), Some(11 + 3));
+        }
+    }
+
+    #[test]
+    fn svg_places_points_in_the_same_columns() {
+        let (values, moves) = blunder_line();
+        let image = svg(&values, &moves);
+        let x = |c: i32| MARGIN + c * COLUMN;
+        let y = |ply: i32| MARGIN + ply * ROW;
+        for (ply, c, color) in [(0, 3, WIN_COLOR), (1, 7, WIN_COLOR), (2, 8, LOSS_COLOR)] {
+            let circle = format!("<circle cx=\"{}\" cy=\"{}\" r=\"6\" fill=\"{}\">", x(c), y(ply), color);
+            assert!(image.contains(&circle), "{}", circle);
+        }
+        assert!(image.contains(&format!("width=\"{}\"", x(8) + MARGIN)));
+        assert!(image.contains(&format!("height=\"{}\"", y(2) + MARGIN)));
+    }
+}
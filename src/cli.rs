@@ -0,0 +1,544 @@
+use std::collections::HashMap;
+use std::fs;
+use crate::game::*;
+use crate::game::registry::{self, Arguments};
+use crate::analysis::analyze;
+use crate::{ui, tui};
+use crate::database::{table_key, Database};
+use crate::server::Server;
+use crate::engine::Engine;
+use crate::agent::{self, play_match, Agent};
+use crate::tournament::round_robin;
+use crate::rating::Ratings;
+use crate::record::Record;
+use crate::agent::perfect::Perfect;
+use crate::agent::external::ExternalEngine;
+use crate::analysis::{annotate, find_mistakes, print_review, value_history};
+use crate::chart;
+use crate::exercise::{self, find_exercises};
+use uuid::Uuid;
+
+
+pub const USAGE: &str =
+"Usage: game_tree <command> [options]
+
+Commands:
+  solve        Solve a game and print its outcome and a line of perfect play
+  analyze      Solve a game and print a breakdown of all of its positions
+  play         Play a game against the solver in the terminal
+  tui          Play a game against the solver in a full-screen terminal UI
+  query        Print the value of a position and of every move available in it
+  list-games   List the available games and their parameters
+  serve        Serve games and solved positions over HTTP as JSON
+  engine       Speak the line-based engine protocol over stdin and stdout
+  versus       Pit an external engine against the solver, and list its mistakes
+  tournament   Play agents against each other in a round robin
+  ratings      Print the agent ratings kept in a file
+  replay       Replay a recorded match move by move
+  review       Mark every move of a match as best, an inaccuracy or a blunder
+  chart        Draw how the value of a match changed from move to move
+  exercises    Find positions with a single winning move, hardest first
+
+Options:
+  --game <name>      Game to use (see list-games), tic-tac-toe by default
+  --<parameter> <n>  Value of one of the game's parameters, as in --coins 20
+  --moves <list>     Comma-separated moves to play before a query (or to review
+                     or chart without a record), as in a1,b2
+  --position <text>  Position to start from instead of the usual one, as in
+                     'O.X/.O./... X' for tic-tac-toe or '7' for coin games
+  --database <path>  File to save the solved table to (solve and analyze), or
+                     comma-separated files to load solved tables from (serve)
+  --port <n>         Port for the server to listen on, 8000 by default
+  --engine <command> Program speaking the engine protocol, for versus
+  --agents <list>    Comma-separated agents for a tournament (see below)
+  --rounds <n>       Matches between each pair of agents, 10 by default
+  --seed <n>         Seed for agents which make random choices, 0 by default
+  --ratings <path>   File where a tournament updates the agents' ratings
+  --record <path>    Recorded match to replay, review or chart
+  --svg <path>       File to also save a chart to, as an SVG image
+  --count <n>        Most exercises to list, 10 by default
+  --export <path>    File to also save the exercises to, one per line";
+
+
+// Usage along with the agents that tournaments accept.
+pub fn usage() -> String {
+    let specs: Vec<String> = agent::AGENT_SPECS
+        .lines()
+        .map(|line| format!("  {}", line))
+        .collect();
+    format!("{}\n\nAgents:\n{}", USAGE, specs.join("\n"))
+}
+
+
+// Parsed command line arguments. Game parameters which are not given are
+// left out, so that the game fills in its own defaults.
+pub struct Options {
+    pub command: String,
+    pub game: Option<String>,
+    pub arguments: Arguments,
+    pub moves: Vec<String>,
+    pub position: Option<String>,
+    pub databases: Vec<String>,
+    pub port: u16,
+    pub engine: Option<String>,
+    pub agents: Vec<String>,
+    pub rounds: u32,
+    pub seed: u64,
+    pub ratings: Option<String>,
+    pub record: Option<String>,
+    pub svg: Option<String>,
+    pub count: usize,
+    pub export: Option<String>
+}
+
+
+// Runs the program as instructed by the arguments (without the binary name).
+pub fn run(args: &[String]) -> Result<(), String> {
+    let options = parse(args)?;
+    match options.command.as_str() {
+        "solve" => solve_command(&options),
+        "analyze" => analyze_command(&options),
+        "play" => play_command(&options),
+        "tui" => {
+            let id = options.game.as_deref().unwrap_or("tic-tac-toe");
+            tui::run(id, &options.arguments, options.position.as_deref())
+        },
+        "query" => query_command(&options),
+        "serve" => serve_command(&options),
+        "versus" => versus_command(&options),
+        "tournament" => tournament_command(&options),
+        "replay" => replay_command(&options),
+        "review" => review_command(&options),
+        "chart" => chart_command(&options),
+        "exercises" => exercises_command(&options),
+        "ratings" => {
+            let path = options.ratings.as_deref().ok_or("Expected a --ratings file.")?;
+            Ratings::load(path)?.print();
+            Ok(())
+        },
+        "engine" => {
+            Engine::new().run();
+            Ok(())
+        },
+        "list-games" => {
+            list_games();
+            Ok(())
+        },
+        "help" => {
+            println!("{}", usage());
+            Ok(())
+        },
+        other => Err(format!("Unknown command '{}'.", other))
+    }
+}
+
+
+pub fn parse(args: &[String]) -> Result<Options, String> {
+    let mut options = Options {
+        command: String::new(),
+        game: None,
+        arguments: Arguments::new(),
+        moves: Vec::new(),
+        position: None,
+        databases: Vec::new(),
+        port: 8000,
+        engine: None,
+        agents: Vec::new(),
+        rounds: 10,
+        seed: 0,
+        ratings: None,
+        record: None,
+        svg: None,
+        count: 10,
+        export: None
+    };
+    let mut rest = args.iter();
+    options.command = match rest.next() {
+        Some(command) => command.clone(),
+        None => return Err("Missing command.".to_string())
+    };
+    while let Some(flag) = rest.next() {
+        let value = match rest.next() {
+            Some(value) => value,
+            None => return Err(format!("Missing value for {}.", flag))
+        };
+        match flag.as_str() {
+            "--game" => options.game = Some(value.clone()),
+            "--moves" => options.moves = list(value),
+            "--position" => options.position = Some(value.clone()),
+            "--database" => options.databases = list(value),
+            "--engine" => options.engine = Some(value.clone()),
+            "--agents" => options.agents = list(value),
+            "--rounds" => {
+                options.rounds = match value.parse() {
+                    Ok(rounds) if rounds > 0 => rounds,
+                    _ => return Err(format!("Expected a positive number of rounds, got '{}'.", value))
+                }
+            },
+            "--seed" => {
+                options.seed = value
+                    .parse()
+                    .map_err(|_| format!("Expected a non-negative seed, got '{}'.", value))?
+            },
+            "--ratings" => options.ratings = Some(value.clone()),
+            "--record" => options.record = Some(value.clone()),
+            "--svg" => options.svg = Some(value.clone()),
+            "--count" => {
+                options.count = value
+                    .parse()
+                    .map_err(|_| format!("Expected a non-negative count, got '{}'.", value))?
+            },
+            "--export" => options.export = Some(value.clone()),
+            "--port" => {
+                options.port = value
+                    .parse()
+                    .map_err(|_| format!("Invalid port '{}'.", value))?
+            },
+            _ => match flag.strip_prefix("--") {
+                Some(name) => {
+                    options.arguments.insert(name.to_string(), number(flag, value)?);
+                },
+                None => return Err(format!("Unknown option {}.", flag))
+            }
+        }
+    }
+    // Databases are looked up by game and arguments alone, so they must hold
+    // the table of the whole game rather than that of a single position.
+    if options.position.is_some() && !options.databases.is_empty() {
+        return Err("Tables solved from a --position cannot be saved to a --database.".to_string())
+    }
+    Ok(options)
+}
+
+
+// Builds the game named in the options, checking its parameters first, and
+// sets up the position given in the options if there is one.
+pub fn build_game(options: &Options) -> Result<Box<dyn Game>, String> {
+    let id = options.game.as_deref().unwrap_or("tic-tac-toe");
+    let entry = registry::find(id).ok_or(format!("Unknown game '{}'. Try list-games.", id))?;
+    match options.position.as_deref() {
+        Some(position) => entry.setup(&options.arguments, position),
+        None => entry.build(&options.arguments)
+    }
+}
+
+
+/* COMMANDS */
+
+fn solve_command(options: &Options) -> Result<(), String> {
+    let mut game = build_game(options)?;
+    let mut seen: HashMap<i32, Outcome> = HashMap::new();
+    let result = solve(game.as_mut(), &mut seen);
+    seen.insert(game.state(), result);
+    save_table(options, &seen)?;
+    println!("{}\n", game.render());
+    println!("The player to move has a {}.", ui::describe(result));
+    println!("\nPerfect play:\n");
+    for mv in principal_variation(game.as_mut(), &seen) {
+        println!("{}", game.encode_move(mv));
+        game.play(mv);
+        println!("{}\n", game.render());
+    }
+    Ok(())
+}
+
+fn analyze_command(options: &Options) -> Result<(), String> {
+    let mut game = build_game(options)?;
+    let mut seen: HashMap<i32, Outcome> = HashMap::new();
+    let result = solve(game.as_mut(), &mut seen);
+    seen.insert(game.state(), result);
+    save_table(options, &seen)?;
+    analyze(&seen);
+    println!("The player to move has a {}.", ui::describe(result));
+    Ok(())
+}
+
+// Without a game to play, lets the player pick one from a menu instead.
+fn play_command(options: &Options) -> Result<(), String> {
+    if options.game.is_none() {
+        ui::play();
+        return Ok(())
+    }
+    let id = options.game.as_deref().unwrap_or("tic-tac-toe");
+    let entry = registry::find(id).ok_or(format!("Unknown game '{}'.", id))?;
+    let arguments = entry.complete(&options.arguments)?;
+    ui::play_game(id, &arguments, build_game(options)?);
+    Ok(())
+}
+
+fn query_command(options: &Options) -> Result<(), String> {
+    let mut game = build_game(options)?;
+    for text in options.moves.iter() {
+        if game.outcome().is_some() {
+            return Err(format!("The game is over before '{}'.", text))
+        }
+        match game.decode_move(text) {
+            Some(mv) => game.play(mv),
+            None => return Err(format!("'{}' is not a legal move.", text))
+        }
+    }
+    let mut seen: HashMap<i32, Outcome> = HashMap::new();
+    let result = solve(game.as_mut(), &mut seen);
+    println!("{}\n", game.render());
+    println!("The player to move has a {}.", ui::describe(result));
+    for (mv, out) in move_values(game.as_mut(), &seen) {
+        println!("  {:>6}: {}", game.encode_move(mv), ui::describe(out));
+    }
+    Ok(())
+}
+
+fn serve_command(options: &Options) -> Result<(), String> {
+    let mut databases = Vec::new();
+    for path in options.databases.iter() {
+        databases.push(Database::load(path)?);
+    }
+    Server::new(databases).listen(options.port)
+}
+
+// Plays the external engine against the solver once from each side, from
+// the given position if there is one.
+fn versus_command(options: &Options) -> Result<(), String> {
+    let command = options.engine.as_deref().ok_or("Expected an --engine to play against.")?;
+    let id = options.game.as_deref().unwrap_or("tic-tac-toe");
+    let entry = registry::find(id).ok_or(format!("Unknown game '{}'.", id))?;
+    let arguments = entry.complete(&options.arguments)?;
+    let mut game = build_game(options)?;
+    let mut seen: HashMap<i32, Outcome> = HashMap::new();
+    let out = solve(game.as_mut(), &mut seen);
+    seen.insert(game.state(), out);
+    let mut engine = ExternalEngine::new(command, id, &arguments)?;
+    if let Some(position) = options.position.as_deref() {
+        engine.send(&format!("setup {}", position))?;
+    }
+    let mut perfect = Perfect::with_table(seen.clone());
+    for engine_first in [true, false] {
+        let (moves, out, forfeit) = if engine_first {
+            play_match(game.as_mut(), &mut engine, &mut perfect)
+        } else {
+            play_match(game.as_mut(), &mut perfect, &mut engine)
+        };
+        let side = if engine_first { 0 } else { 1 };
+        let order = if engine_first { "first" } else { "second" };
+        println!("{} moving {}: {}", engine.name(), order, replay(game.as_mut(), &moves).join(" "));
+        let result = match if engine_first { out } else { flip(out) } {
+            Outcome::Win(_) => "win",
+            Outcome::Loss(_) => "loss",
+            Outcome::Tie(_) => "tie"
+        };
+        println!("Result for {}: {}", engine.name(), result);
+        if let Some(message) = forfeit {
+            println!("Forfeited: {}", message);
+        }
+        let mistakes: Vec<_> = find_mistakes(game.as_mut(), &moves, &seen)?
+            .into_iter()
+            .filter(|m| m.ply % 2 == side)
+            .collect();
+        if mistakes.is_empty() {
+            println!("No mistakes.");
+        }
+        for m in mistakes {
+            println!(
+                "Mistake on move {}: {} gives a {} instead of a {}.",
+                m.ply + 1, m.notation, ui::describe(m.played), ui::describe(m.best)
+            );
+        }
+        println!();
+    }
+    Ok(())
+}
+
+fn tournament_command(options: &Options) -> Result<(), String> {
+    if options.agents.len() < 2 {
+        return Err("Expected at least two --agents.".to_string())
+    }
+    let mut game = build_game(options)?;
+    let mut agents = Vec::new();
+    for (i, spec) in options.agents.iter().enumerate() {
+        agents.push(agent::from_spec(spec, options.seed.wrapping_add(i as u64))?);
+    }
+    let results = round_robin(game.as_mut(), &mut agents, options.rounds);
+    results.print();
+    if let Some(path) = options.ratings.as_deref() {
+        let id = options.game.as_deref().unwrap_or("tic-tac-toe");
+        let entry = registry::find(id).ok_or(format!("Unknown game '{}'.", id))?;
+        // Ratings earned from a set up position only compare with each other
+        let mut key = table_key(id, &entry.complete(&options.arguments)?);
+        if let Some(position) = options.position.as_deref() {
+            key = format!("{} from {}", key, position);
+        }
+        let mut ratings = Ratings::load(path)?;
+        ratings.set_game(&key)?;
+        ratings.update(&results);
+        ratings.save(path)?;
+        println!();
+        ratings.print();
+    }
+    Ok(())
+}
+
+fn replay_command(options: &Options) -> Result<(), String> {
+    let path = options.record.as_deref().ok_or("Expected a --record to replay.")?;
+    let record = Record::load(path)?;
+    // Replaying checks every move before anything is printed
+    let mut game = record.replay()?;
+    for _ in record.moves.iter() {
+        game.undo();
+    }
+    for name in ["Game", "Parameters", "First", "Second", "Date", "Result"] {
+        println!("{:<12}{}", name, record.tag(name).unwrap_or("?"));
+    }
+    if let Some(position) = record.tag("Position") {
+        println!("{:<12}{}", "Position", position);
+    }
+    println!("\n{}\n", game.render());
+    for (ply, text) in record.moves.iter().enumerate() {
+        let mv = game.decode_move(text).expect("Move was legal a moment ago.");
+        game.play(mv);
+        println!("{}. {}\n{}\n", ply + 1, text, game.render());
+    }
+    Ok(())
+}
+
+fn review_command(options: &Options) -> Result<(), String> {
+    let (mut game, moves, seen) = load_match(options)?;
+    print_review(&annotate(game.as_mut(), &moves, &seen)?);
+    Ok(())
+}
+
+fn chart_command(options: &Options) -> Result<(), String> {
+    let (mut game, moves, seen) = load_match(options)?;
+    let notation = replay(game.as_mut(), &moves);
+    for mv in moves.iter() {
+        game.play(*mv);
+    }
+    let values = value_history(game.as_mut(), &seen);
+    println!("{}", chart::ascii(&values, &notation));
+    if let Some(path) = options.svg.as_deref() {
+        fs::write(path, chart::svg(&values, &notation))
+            .map_err(|e| format!("Could not write {}: {}", path, e))?;
+        println!("\nSaved the chart to {}.", path);
+    }
+    Ok(())
+}
+
+fn exercises_command(options: &Options) -> Result<(), String> {
+    let mut game = build_game(options)?;
+    let mut seen: HashMap<i32, Outcome> = HashMap::new();
+    let out = solve(game.as_mut(), &mut seen);
+    seen.insert(game.state(), out);
+    let mut exercises = find_exercises(game.as_mut(), &seen);
+    let found = exercises.len();
+    exercises.truncate(options.count);
+    println!("Found {} exercises, showing the {} hardest.\n", found, exercises.len());
+    exercise::print_sheet(&exercises);
+    if let Some(path) = options.export.as_deref() {
+        exercise::export(&exercises, path)?;
+        println!("\nSaved {} exercises to {}.", exercises.len(), path);
+    }
+    Ok(())
+}
+
+fn list_games() {
+    for entry in registry::games() {
+        println!("{} ({})", entry.id, entry.name);
+        for p in entry.parameters.iter() {
+            println!(
+                "  --{:<10} {} ({} to {}, default {})",
+                p.name, p.description, p.min, p.max, p.default
+            );
+        }
+    }
+}
+
+
+/* HELPER FUNCTIONS */
+
+// Writes out moves in notation, leaving the game as it was.
+fn replay(game: &mut dyn Game, moves: &[Uuid]) -> Vec<String> {
+    let mut notation = Vec::new();
+    for mv in moves {
+        notation.push(game.encode_move(*mv));
+        game.play(*mv);
+    }
+    for _ in moves {
+        game.undo();
+    }
+    notation
+}
+
+fn flip(out: Outcome) -> Outcome {
+    match out {
+        Outcome::Win(rem) => Outcome::Loss(rem),
+        Outcome::Loss(rem) => Outcome::Win(rem),
+        tie => tie
+    }
+}
+
+// A game at its first position, the moves played from there, and the table
+// solved from there.
+type Match = (Box<dyn Game>, Vec<Uuid>, HashMap<i32, Outcome>);
+
+// Loads the match to look at, from a record or else from the moves given in
+// the options, and solves it.
+fn load_match(options: &Options) -> Result<Match, String> {
+    let (mut game, texts) = match options.record.as_deref() {
+        Some(path) => {
+            let record = Record::load(path)?;
+            let mut game = record.replay()?;
+            for _ in record.moves.iter() {
+                game.undo();
+            }
+            (game, record.moves)
+        },
+        None => (build_game(options)?, options.moves.clone())
+    };
+    let mut moves = Vec::new();
+    for text in texts.iter() {
+        if game.outcome().is_some() {
+            return Err(format!("The game is over before '{}'.", text))
+        }
+        match game.decode_move(text) {
+            Some(mv) => {
+                game.play(mv);
+                moves.push(mv);
+            },
+            None => return Err(format!("'{}' is not a legal move.", text))
+        }
+    }
+    for _ in moves.iter() {
+        game.undo();
+    }
+    let mut seen: HashMap<i32, Outcome> = HashMap::new();
+    let out = solve(game.as_mut(), &mut seen);
+    seen.insert(game.state(), out);
+    Ok((game, moves, seen))
+}
+
+// Saves a solved table when the options ask for it.
+fn save_table(options: &Options, seen: &HashMap<i32, Outcome>) -> Result<(), String> {
+    let path = match options.databases.as_slice() {
+        [] => return Ok(()),
+        [path] => path,
+        _ => return Err("Only one database can be saved at a time.".to_string())
+    };
+    let id = options.game.as_deref().unwrap_or("tic-tac-toe");
+    let entry = registry::find(id).ok_or(format!("Unknown game '{}'.", id))?;
+    let arguments = entry.complete(&options.arguments)?;
+    Database::new(id, &arguments, seen.clone()).save(path)?;
+    println!("Saved {} positions to {}.\n", seen.len(), path);
+    Ok(())
+}
+
+fn list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+fn number(flag: &str, value: &str) -> Result<i32, String> {
+    value
+        .parse()
+        .map_err(|_| format!("Expected a number for {}, got '{}'.", flag, value))
+}
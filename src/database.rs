@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+use std::fs;
+use crate::game::Outcome;
+use crate::game::registry::Arguments;
+
+
+// A solved table saved to disk, along with the game and parameters it was
+// solved for. States are only meaningful for that exact game.
+pub struct Database {
+    pub game: String,
+    pub arguments: Arguments,
+    pub table: HashMap<i32, Outcome>
+}
+
+impl Database {
+    pub fn new(game: &str, arguments: &Arguments, table: HashMap<i32, Outcome>) -> Self {
+        Database {
+            game: game.to_string(),
+            arguments: arguments.clone(),
+            table
+        }
+    }
+
+    // Identifies the game and parameters, regardless of argument order.
+    pub fn key(&self) -> String {
+        table_key(&self.game, &self.arguments)
+    }
+
+    // Plain text, with a header naming the game and its parameters and then
+    // one line per position holding its state, outcome and remoteness.
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let mut lines = vec![format!("game {}", self.game)];
+        let mut arguments: Vec<_> = self.arguments.iter().collect();
+        arguments.sort();
+        for (name, value) in arguments {
+            lines.push(format!("{} {}", name, value));
+        }
+        lines.push("---".to_string());
+        let mut states: Vec<_> = self.table.iter().collect();
+        states.sort_by_key(|(state, _)| **state);
+        for (state, out) in states {
+            let (letter, rem) = match out {
+                Outcome::Win(rem) => ('W', rem),
+                Outcome::Loss(rem) => ('L', rem),
+                Outcome::Tie(rem) => ('T', rem)
+            };
+            lines.push(format!("{} {} {}", state, letter, rem));
+        }
+        lines.push(String::new());
+        fs::write(path, lines.join("\n"))
+            .map_err(|e| format!("Could not write {}: {}", path, e))
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Could not read {}: {}", path, e))?;
+        let invalid = |line: &str| format!("Invalid line in {}: '{}'", path, line);
+        let mut lines = text.lines();
+        let game = match lines.next().and_then(|l| l.strip_prefix("game ")) {
+            Some(game) => game.trim().to_string(),
+            None => return Err(format!("{} is not a solved database.", path))
+        };
+        let mut arguments = Arguments::new();
+        for line in lines.by_ref() {
+            if line == "---" {
+                break
+            }
+            let (name, value) = line.split_once(' ').ok_or_else(|| invalid(line))?;
+            let value = value.parse().map_err(|_| invalid(line))?;
+            arguments.insert(name.to_string(), value);
+        }
+        let mut table = HashMap::new();
+        for line in lines.filter(|l| !l.is_empty()) {
+            let parts: Vec<&str> = line.split(' ').collect();
+            if parts.len() != 3 {
+                return Err(invalid(line))
+            }
+            let state: i32 = parts[0].parse().map_err(|_| invalid(line))?;
+            let rem: i32 = parts[2].parse().map_err(|_| invalid(line))?;
+            let out = match parts[1] {
+                "W" => Outcome::Win(rem),
+                "L" => Outcome::Loss(rem),
+                "T" => Outcome::Tie(rem),
+                _ => return Err(invalid(line))
+            };
+            table.insert(state, out);
+        }
+        Ok(Database { game, arguments, table })
+    }
+}
+
+
+// Builds the key under which the table for a game and its parameters is kept.
+pub fn table_key(game: &str, arguments: &Arguments) -> String {
+    let mut arguments: Vec<_> = arguments.iter().collect();
+    arguments.sort();
+    let parts: Vec<String> = arguments
+        .into_iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect();
+    format!("{}({})", game, parts.join(","))
+}
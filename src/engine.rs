@@ -0,0 +1,280 @@
+use std::io::{self, BufRead, Write};
+use std::collections::HashMap;
+use crate::game::*;
+use crate::game::registry::{self, Arguments};
+
+
+// Commands understood by the engine, one per line. Responses follow GTP:
+// they start with "= " on success or "? " on failure, and end with an empty
+// line, so that they can span several lines.
+pub const COMMANDS: &str =
+"name                          Name of the engine
+games                         Ids of the available games
+game <id> [<name>=<n> ...]    Select and solve a game, as in: game tic-tac-toe win=3
+setup <position>              Start from a position in the game's notation, as in: setup O.X/.O./... X
+position [<move> ...]         Set up the position reached by these moves from the start (or setup)
+play <move>                   Play a single move from the current position
+moves                         Legal moves in the current position
+bestmove                      One of the best moves in the current position
+value                         Outcome and remoteness for the player to move
+board                         Plain text rendering of the current position
+help                          This list of commands
+quit                          Stop the engine";
+
+pub const ENGINE_NAME: &str = "game_tree";
+
+
+// The selected game, the moves played on it so far, and its solved table.
+pub struct Engine {
+    game: Option<Box<dyn Game>>,
+    game_id: String,
+    arguments: Arguments,
+    seen: HashMap<i32, Outcome>,
+    plies: usize
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Engine {
+    pub fn new() -> Self {
+        Engine {
+            game: None,
+            game_id: String::new(),
+            arguments: Arguments::new(),
+            seen: HashMap::new(),
+            plies: 0
+        }
+    }
+
+    // Answers commands from standard input until told to quit or until the
+    // input runs out.
+    pub fn run(&mut self) {
+        let stdin = io::stdin();
+        let mut stdout = io::stdout();
+        for line in stdin.lock().lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(_) => return
+            };
+            if line.trim().is_empty() {
+                continue
+            }
+            let response = match self.execute(&line) {
+                Ok(text) if text.is_empty() => "=".to_string(),
+                Ok(text) => format!("= {}", text),
+                Err(message) => format!("? {}", message)
+            };
+            write!(stdout, "{}\n\n", response).ok();
+            stdout.flush().ok();
+            if line.trim() == "quit" {
+                return
+            }
+        }
+    }
+
+    // Carries out a single command, returning the body of the response.
+    pub fn execute(&mut self, line: &str) -> Result<String, String> {
+        let mut words = line.split_whitespace();
+        let command = words.next().unwrap_or("");
+        let args: Vec<&str> = words.collect();
+        match command {
+            "name" => Ok(ENGINE_NAME.to_string()),
+            "games" => {
+                let ids: Vec<&str> = registry::games().iter().map(|e| e.id).collect();
+                Ok(ids.join(" "))
+            },
+            "game" => self.select(&args),
+            "setup" => self.setup(&args.join(" ")),
+            "position" => {
+                self.rewind()?;
+                for text in args {
+                    self.play(text)?;
+                }
+                Ok(String::new())
+            },
+            "play" => match args.as_slice() {
+                [text] => self.play(text).map(|_| String::new()),
+                _ => Err("Expected exactly one move.".to_string())
+            },
+            "moves" => {
+                let game = self.game()?;
+                if game.outcome().is_some() {
+                    return Err("The game is over.".to_string())
+                }
+                let moves: Vec<String> = game
+                    .possible_moves()
+                    .into_iter()
+                    .map(|mv| game.encode_move(mv))
+                    .collect();
+                Ok(moves.join(" "))
+            },
+            "bestmove" => {
+                let seen = &self.seen;
+                let game = self.game.as_mut().ok_or("No game selected.")?;
+                if game.outcome().is_some() {
+                    return Err("The game is over.".to_string())
+                }
+                match move_values(game.as_mut(), seen).first() {
+                    Some((mv, _)) => Ok(game.encode_move(*mv)),
+                    None => Err("There are no moves to play.".to_string())
+                }
+            },
+            "value" => {
+                let game = self.game()?;
+                let out = match game.outcome() {
+                    Some(out) => out,
+                    None => self.seen[&game.state()]
+                };
+                Ok(match out {
+                    Outcome::Win(rem) => format!("win {}", rem),
+                    Outcome::Loss(rem) => format!("loss {}", rem),
+                    Outcome::Tie(rem) => format!("tie {}", rem)
+                })
+            },
+            "board" => Ok(format!("\n{}", self.game()?.render())),
+            "help" => Ok(format!("\n{}", COMMANDS)),
+            "quit" => Ok(String::new()),
+            _ => Err(format!("Unknown command '{}'.", command))
+        }
+    }
+
+    fn select(&mut self, args: &[&str]) -> Result<String, String> {
+        let (id, rest) = args.split_first().ok_or("Expected a game.")?;
+        let entry = registry::find(id).ok_or(format!("Unknown game '{}'.", id))?;
+        let mut arguments = Arguments::new();
+        for pair in rest {
+            let (name, value) = pair
+                .split_once('=')
+                .ok_or(format!("Expected <name>=<n>, got '{}'.", pair))?;
+            let value = value
+                .parse()
+                .map_err(|_| format!("Expected a number for {}, got '{}'.", name, value))?;
+            arguments.insert(name.to_string(), value);
+        }
+        let mut game = entry.build(&arguments)?;
+        let mut seen = HashMap::new();
+        let out = solve(game.as_mut(), &mut seen);
+        seen.insert(game.state(), out);
+        self.game = Some(game);
+        self.game_id = id.to_string();
+        self.arguments = arguments;
+        self.seen = seen;
+        self.plies = 0;
+        Ok(String::new())
+    }
+
+    // Replaces the selected game's session with one starting from the given
+    // position, solving whatever part of it the table does not cover yet.
+    fn setup(&mut self, position: &str) -> Result<String, String> {
+        if self.game.is_none() {
+            return Err("No game selected.".to_string())
+        }
+        let entry = registry::find(&self.game_id).ok_or("No game selected.")?;
+        let mut game = entry.setup(&self.arguments, position)?;
+        if game.outcome().is_none() && !self.seen.contains_key(&game.state()) {
+            let out = solve(game.as_mut(), &mut self.seen);
+            self.seen.insert(game.state(), out);
+        }
+        self.game = Some(game);
+        self.plies = 0;
+        Ok(String::new())
+    }
+
+    fn play(&mut self, text: &str) -> Result<(), String> {
+        let game = self.game.as_mut().ok_or("No game selected.")?;
+        if game.outcome().is_some() {
+            return Err("The game is over.".to_string())
+        }
+        match game.decode_move(text) {
+            Some(mv) => {
+                game.play(mv);
+                self.plies += 1;
+                Ok(())
+            },
+            None => Err(format!("'{}' is not a legal move.", text))
+        }
+    }
+
+    fn rewind(&mut self) -> Result<(), String> {
+        let game = self.game.as_mut().ok_or("No game selected.")?;
+        for _ in 0..self.plies {
+            game.undo();
+        }
+        self.plies = 0;
+        Ok(())
+    }
+
+    fn game(&self) -> Result<&dyn Game, String> {
+        match &self.game {
+            Some(game) => Ok(game.as_ref()),
+            None => Err("No game selected.".to_string())
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Each command along with the response it should get.
+    fn check(engine: &mut Engine, transcript: &[(&str, Result<&str, &str>)]) {
+        for (line, expected) in transcript {
+            let response = engine.execute(line);
+            let expected = expected.map(str::to_string).map_err(str::to_string);
+            assert_eq!(response, expected, "{}", line);
+        }
+    }
+
+    #[test]
+    fn answers_a_session() {
+        let mut engine = Engine::new();
+        check(&mut engine, &[
+            ("bestmove", Err("No game selected.")),
+            ("setup OO./XX./... O", Err("No game selected.")),
+            ("game tic-tac-toe win=3", Ok("")),
+            ("value", Ok("tie 9")),
+            ("setup OO./XX./... O", Ok("")),
+            ("value", Ok("win 1")),
+            ("bestmove", Ok("c1")),
+            ("position b3", Ok("")),
+            ("value", Ok("win 1")),
+            ("bestmove", Ok("c2")),
+            ("position", Ok("")),
+            ("value", Ok("win 1")),
+            ("position c1", Ok("")),
+            ("value", Ok("loss 0")),
+            ("moves", Err("The game is over.")),
+            ("bestmove", Err("The game is over.")),
+            ("play a3", Err("The game is over.")),
+            ("position c1 a3", Err("The game is over.")),
+            ("position z9", Err("'z9' is not a legal move.")),
+            ("play", Err("Expected exactly one move.")),
+            ("frobnicate", Err("Unknown command 'frobnicate'."))
+        ]);
+    }
+
+    #[test]
+    fn replays_positions_from_the_start() {
+        let mut engine = Engine::new();
+        check(&mut engine, &[
+            ("game zero-by-1-2 coins=4", Ok("")),
+            ("moves", Ok("1 2")),
+            ("bestmove", Ok("1")),
+            ("position 2", Ok("")),
+            ("value", Ok("win 1")),
+            ("bestmove", Ok("2")),
+            ("position 1 1", Ok("")),
+            ("value", Ok("win 1")),
+            ("position 1 2 1", Ok("")),
+            ("value", Ok("loss 0")),
+            ("setup 3", Ok("")),
+            ("value", Ok("loss 2")),
+            ("game zero-by-1-2 coins=x", Err("Expected a number for coins, got 'x'."))
+        ]);
+    }
+}
@@ -0,0 +1,170 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use crate::game::{move_values, Game, Outcome};
+
+
+// A "find the winning move" exercise: a position where exactly one of the
+// moves available wins. Tempting moves are the wrong answers which do not
+// get punished right away, that is, the ones which tie or which lose in
+// more than one reply.
+pub struct Exercise {
+    pub position: String,
+    pub board: String,
+    pub solution: String,
+    pub remoteness: i32,
+    pub tempting: usize,
+    pub moves: usize
+}
+
+
+// Looks through every position reachable from the game's current one for
+// exercises, according to a table filled by solve. Positions which are the
+// same up to the game's symmetries only show up once. Exercises are sorted
+// from hardest to easiest: the longest wins first, and then the ones with
+// the most tempting moves. Leaves the game as it found it.
+pub fn find_exercises(game: &mut dyn Game, seen: &HashMap<i32, Outcome>) -> Vec<Exercise> {
+    let mut exercises = Vec::new();
+    let mut visited = HashSet::new();
+    let mut canonical = HashSet::new();
+    search(game, seen, &mut visited, &mut canonical, &mut exercises);
+    exercises.sort_by(|a, b| {
+        (b.remoteness, b.tempting)
+            .cmp(&(a.remoteness, a.tempting))
+            .then_with(|| a.position.cmp(&b.position))
+    });
+    exercises
+}
+
+
+// One exercise per line, with the position, the winning move, the number of
+// moves until the win, and the number of tempting moves separated by tabs.
+pub fn export(exercises: &[Exercise], path: &str) -> Result<(), String> {
+    let mut lines: Vec<String> = exercises
+        .iter()
+        .map(|e| format!("{}\t{}\t{}\t{}", e.position, e.solution, e.remoteness, e.tempting))
+        .collect();
+    lines.push(String::new());
+    fs::write(path, lines.join("\n"))
+        .map_err(|e| format!("Could not write {}: {}", path, e))
+}
+
+
+// Prints an exercise sheet with the boards first and the answers at the end.
+pub fn print_sheet(exercises: &[Exercise]) {
+    for (i, e) in exercises.iter().enumerate() {
+        println!(
+            "Exercise {}: win in {} ({} moves, {} tempting)",
+            i + 1, e.remoteness, e.moves, e.tempting
+        );
+        println!("Position: {}\n", e.position);
+        println!("{}\n", e.board);
+    }
+    println!("Answers:");
+    for (i, e) in exercises.iter().enumerate() {
+        println!("  {}. {}", i + 1, e.solution);
+    }
+}
+
+
+/* HELPER FUNCTIONS */
+
+fn search(
+    game: &mut dyn Game,
+    seen: &HashMap<i32, Outcome>,
+    visited: &mut HashSet<i32>,
+    canonical: &mut HashSet<String>,
+    exercises: &mut Vec<Exercise>
+) {
+    if !visited.insert(game.state()) || game.outcome().is_some() {
+        return
+    }
+    let values = move_values(game, seen);
+    let winning = values
+        .iter()
+        .filter(|(_, out)| matches!(out, Outcome::Win(_)))
+        .count();
+    if let (1, [(solution, Outcome::Win(rem)), wrong @ ..]) = (winning, values.as_slice()) {
+        if !wrong.is_empty() && canonical.insert(game.canonical_position()) {
+            exercises.push(Exercise {
+                position: game.position(),
+                board: game.render(),
+                solution: game.encode_move(*solution),
+                remoteness: *rem,
+                tempting: wrong.iter().filter(|(_, out)| tempting(*out)).count(),
+                moves: values.len()
+            });
+        }
+    }
+    for (mv, _) in values {
+        game.play(mv);
+        search(game, seen, visited, canonical, exercises);
+        game.undo();
+    }
+}
+
+// Wrong moves are tempting unless the opponent can win on their next move.
+fn tempting(out: Outcome) -> bool {
+    match out {
+        Outcome::Loss(rem) => rem > 2,
+        _ => true
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{solve, tic_tac_toe, zero_by_1_2};
+
+    fn exercises_from(game: &mut dyn Game) -> Vec<Exercise> {
+        let mut seen = HashMap::new();
+        solve(game, &mut seen);
+        let start = game.position();
+        let exercises = find_exercises(game, &seen);
+        assert_eq!(game.position(), start);
+        exercises
+    }
+
+    #[test]
+    fn tic_tac_toe_exercises_have_one_winning_move() {
+        let exercises = exercises_from(&mut tic_tac_toe::Session::new(3, 3, 3));
+        assert!(!exercises.is_empty());
+        let mut canonical = HashSet::new();
+        for e in exercises.iter() {
+            // Solved again from scratch, each position has exactly one
+            // winning move, and it is the one given as the answer
+            let mut game = tic_tac_toe::Session::from_position(&e.position, 3).unwrap();
+            let mut seen = HashMap::new();
+            solve(&mut game, &mut seen);
+            let values = move_values(&mut game, &seen);
+            let winning: Vec<String> = values
+                .iter()
+                .filter(|(_, out)| matches!(out, Outcome::Win(_)))
+                .map(|(mv, _)| game.encode_move(*mv))
+                .collect();
+            assert_eq!(winning, vec![e.solution.clone()], "{}", e.position);
+            assert!(values[0].1 == Outcome::Win(e.remoteness), "{}", e.position);
+            assert_eq!(e.moves, values.len());
+            assert!(e.moves > 1);
+            let tempting = values[1..].iter().filter(|(_, out)| tempting(*out)).count();
+            assert_eq!(e.tempting, tempting, "{}", e.position);
+            // Mirror images and rotations of a position show up only once
+            assert!(canonical.insert(game.canonical_position()), "{}", e.position);
+        }
+        for pair in exercises.windows(2) {
+            assert!((pair[0].remoteness, pair[0].tempting) >= (pair[1].remoteness, pair[1].tempting));
+        }
+    }
+
+    #[test]
+    fn zero_by_exercises_skip_forced_moves() {
+        // One coin leaves a single move, so it is not an exercise, and
+        // multiples of three have no winning move at all
+        let exercises = exercises_from(&mut zero_by_1_2::Session::new(8));
+        let found: Vec<(&str, &str, i32)> = exercises
+            .iter()
+            .map(|e| (e.position.as_str(), e.solution.as_str(), e.remoteness))
+            .collect();
+        assert_eq!(found, vec![("7", "1", 5), ("8", "2", 5), ("5", "2", 3), ("4", "1", 3), ("2", "2", 1)]);
+    }
+}
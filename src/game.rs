@@ -6,6 +6,37 @@
 pub mod zero_by_1_2;
 pub mod zero_by_1_3_4;
 pub mod tic_tac_toe;
+pub mod domineering;
+pub mod dice_race;
+pub mod pig;
+pub mod kuhn_poker;
+pub mod leduc_holdem;
+pub mod hanoi;
+pub mod sliding_tiles;
+
+/* ---------- GAME COMPOUNDS ---------- */
+pub mod compound;
+
+/* ---------- GAME VARIANTS ---------- */
+pub mod misere;
+
+/* ---------- PARTIZAN THEORY ---------- */
+pub mod partizan;
+
+/* ---------- STOCHASTIC GAMES ---------- */
+pub mod stochastic;
+
+/* ---------- IMPERFECT INFORMATION ---------- */
+pub mod extensive;
+
+/* ---------- SIMULTANEOUS MOVES ---------- */
+pub mod normal_form;
+
+/* ---------- PUZZLES ---------- */
+pub mod puzzle;
+
+/* ---------- GAME REGISTRY ---------- */
+pub mod registry;
 
 
 use uuid::Uuid;
@@ -26,10 +57,32 @@ pub trait Game {
     fn state(&self) -> i32;
     fn outcome(&self) -> Option<Outcome>;
     fn possible_moves(&self) -> Vec<Uuid>;
+    fn history(&self) -> Vec<Uuid>;
+    fn encode_move(&self, mv: Uuid) -> String;
+    fn decode_move(&self, text: &str) -> Option<Uuid>;
+    fn render(&self) -> String;
+    fn position(&self) -> String;
+
+    // The same position for every position equivalent to this one under the
+    // game's symmetries, such as rotations of a board.
+    fn canonical_position(&self) -> String {
+        self.position()
+    }
 }
 
 
 pub fn solve(game: &mut dyn Game, seen: &mut HashMap<i32, Outcome>) -> Outcome {
+    solve_with_progress(game, seen, &mut |_| {})
+}
+
+
+// Same as solve, but reports the number of positions in the table every time
+// a new one is added, so that long solves can show how far along they are.
+pub fn solve_with_progress(
+    game: &mut dyn Game,
+    seen: &mut HashMap<i32, Outcome>,
+    progress: &mut dyn FnMut(usize)
+) -> Outcome {
     if let Some(out) = game.outcome() {
         return out
     }
@@ -40,9 +93,10 @@ pub fn solve(game: &mut dyn Game, seen: &mut HashMap<i32, Outcome>) -> Outcome {
         if let Some(out) = seen.get(&encoded_state).copied() {
             possible_outcomes.push(out);
         } else {
-            let out = solve(game, seen);
+            let out = solve_with_progress(game, seen, progress);
             possible_outcomes.push(out);
             seen.insert(encoded_state, out);
+            progress(seen.len());
         }
         game.undo();
     }
@@ -50,6 +104,80 @@ pub fn solve(game: &mut dyn Game, seen: &mut HashMap<i32, Outcome>) -> Outcome {
 }
 
 
+// Annotates every possible move with the outcome it leads to for the player
+// making it, according to a table filled by solve. Moves are sorted from best
+// to worst: fastest wins first, then fastest ties, and then slowest losses.
+// There are no moves to annotate once the game is over.
+pub fn move_values(game: &mut dyn Game, seen: &HashMap<i32, Outcome>) -> Vec<(Uuid, Outcome)> {
+    if game.outcome().is_some() {
+        return Vec::new()
+    }
+    let mut values: Vec<(Uuid, Outcome)> = game
+        .possible_moves()
+        .into_iter()
+        .map(|mv| (mv, mover_outcome(child_outcome(game, mv, seen))))
+        .collect();
+    values.sort_by_key(|(_, out)| preference(*out));
+    values
+}
+
+
+// Walks down the best moves from the current position according to a table
+// filled by solve. Returns the moves in the order they would be played, all
+// the way to the end of the game, and leaves the game as it found it.
+pub fn principal_variation(game: &mut dyn Game, seen: &HashMap<i32, Outcome>) -> Vec<Uuid> {
+    let mut line = Vec::new();
+    while game.outcome().is_none() {
+        match move_values(game, seen).first() {
+            Some((mv, _)) => {
+                game.play(*mv);
+                line.push(*mv);
+            },
+            None => break
+        }
+    }
+    for _ in line.iter() {
+        game.undo();
+    }
+    line
+}
+
+
+// Outcome of the position a move leads to, from the perspective of the
+// player who will be moving there.
+fn child_outcome(game: &mut dyn Game, mv: Uuid, seen: &HashMap<i32, Outcome>) -> Outcome {
+    game.play(mv);
+    let out = match game.outcome() {
+        Some(out) => out,
+        None => *seen.get(&game.state()).expect("Position has not been solved.")
+    };
+    game.undo();
+    out
+}
+
+
+// Translates the outcome of a child position to the perspective of the
+// player who moved into it, counting the move itself.
+fn mover_outcome(child: Outcome) -> Outcome {
+    match child {
+        Outcome::Loss(rem) => Outcome::Win(rem + 1),
+        Outcome::Tie(rem) => Outcome::Tie(rem + 1),
+        Outcome::Win(rem) => Outcome::Loss(rem + 1)
+    }
+}
+
+
+// Sorting key for outcomes, where smaller keys are better for the player
+// the outcome belongs to (mirrors get_outcome).
+fn preference(out: Outcome) -> (i32, i32) {
+    match out {
+        Outcome::Win(rem) => (0, rem),
+        Outcome::Tie(rem) => (1, rem),
+        Outcome::Loss(rem) => (2, -rem)
+    }
+}
+
+
 fn get_outcome(available: Vec<Outcome>) -> Outcome {
     let mut w_rem = i32::MAX;
     let mut t_rem = i32::MAX;
@@ -85,3 +213,151 @@ fn get_outcome(available: Vec<Outcome>) -> Outcome {
         Outcome::Loss(l_rem)
     }
 }
+
+
+/* HELPER FUNCTIONS */
+
+// Board coordinates are written as a column letter followed by a row number,
+// so that "a1" is the top left corner of a board.
+pub fn encode_coordinate(i: i32, j: i32) -> String {
+    format!("{}{}", (b'a' + j as u8) as char, i + 1)
+}
+
+
+pub fn decode_coordinate(text: &str) -> Option<(i32, i32)> {
+    let mut chars = text.trim().chars();
+    let column = chars.next()?.to_ascii_lowercase();
+    if !column.is_ascii_lowercase() {
+        return None
+    }
+    let row: i32 = chars.as_str().parse().ok()?;
+    Some((row - 1, column as i32 - 'a' as i32))
+}
+
+
+// Column letters to print on top of a board of the given width.
+pub fn column_header(width: i32) -> String {
+    let letters: Vec<String> = (0..width)
+        .map(|j| ((b'a' + j as u8) as char).to_string())
+        .collect();
+    format!("    {}", letters.join(" "))
+}
+
+
+// Splits a board position such as "O.X/.O./... X" into its rows of cells
+// (top to bottom, separated by slashes) and the side to move, if given.
+pub fn split_position(text: &str) -> Result<(Vec<Vec<char>>, Option<String>), String> {
+    let mut words = text.split_whitespace();
+    let rows: Vec<Vec<char>> = words
+        .next()
+        .ok_or("Empty position.")?
+        .split('/')
+        .map(|row| row.chars().collect())
+        .collect();
+    let side = words.next().map(|s| s.to_string());
+    if words.next().is_some() {
+        return Err(format!("Unexpected text after the side to move in '{}'.", text))
+    }
+    if rows[0].is_empty() || rows.iter().any(|row| row.len() != rows[0].len()) {
+        return Err("Every row of the position must have the same, non-zero length.".to_string())
+    }
+    Ok((rows, side))
+}
+
+
+// Writes rows of cells back out in the format split_position reads.
+pub fn join_position(rows: Vec<String>, side: &str) -> String {
+    format!("{} {}", rows.join("/"), side)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{tic_tac_toe, zero_by_1_2, zero_by_1_3_4};
+
+    fn remoteness(out: Outcome) -> i32 {
+        match out {
+            Outcome::Win(rem) | Outcome::Loss(rem) | Outcome::Tie(rem) => rem
+        }
+    }
+
+    fn same_kind(a: Outcome, b: Outcome) -> bool {
+        std::mem::discriminant(&a) == std::mem::discriminant(&b)
+    }
+
+    // The principal variation lasts exactly as long as the solved value says,
+    // ends the way it says, and leaves the game where it was.
+    fn check_variation(game: &mut dyn Game) {
+        let mut seen = HashMap::new();
+        let out = solve(game, &mut seen);
+        let start = game.position();
+        let line = principal_variation(game, &seen);
+        assert_eq!(game.position(), start);
+        assert_eq!(line.len() as i32, remoteness(out), "{}", start);
+        for mv in line.iter() {
+            game.play(*mv);
+        }
+        // Seen from whoever is left to move at the end
+        let expected = if line.len().is_multiple_of(2) { out } else { mover_outcome(out) };
+        assert!(same_kind(game.outcome().unwrap(), expected), "{}", start);
+        for _ in line.iter() {
+            game.undo();
+        }
+    }
+
+    #[test]
+    fn principal_variations_follow_the_solved_value() {
+        for coins in 0..12 {
+            check_variation(&mut zero_by_1_2::Session::new(coins));
+            check_variation(&mut zero_by_1_3_4::Session::new(coins));
+        }
+        check_variation(&mut tic_tac_toe::Session::new(3, 3, 3));
+        check_variation(&mut tic_tac_toe::Session::from_position("O../.X./... O", 3).unwrap());
+        check_variation(&mut tic_tac_toe::Session::from_position("OO./XX./... O", 3).unwrap());
+    }
+
+    // Each move is worth what solving the position it leads to from scratch
+    // says, moves come best first, and the best one is worth as much as the
+    // position itself.
+    fn check_values(game: &mut dyn Game) {
+        let mut seen = HashMap::new();
+        let out = solve(game, &mut seen);
+        let values = move_values(game, &seen);
+        assert_eq!(values.len(), game.possible_moves().len());
+        for (mv, value) in values.iter() {
+            game.play(*mv);
+            let child = match game.outcome() {
+                Some(child) => child,
+                None => solve(game, &mut HashMap::new())
+            };
+            game.undo();
+            assert!(*value == mover_outcome(child), "{} {}", game.position(), game.encode_move(*mv));
+        }
+        assert!(values.windows(2).all(|pair| preference(pair[0].1) <= preference(pair[1].1)));
+        if let Some((_, best)) = values.first() {
+            assert!(*best == out, "{}", game.position());
+        }
+    }
+
+    #[test]
+    fn move_values_match_solve_and_come_best_first() {
+        for coins in 0..12 {
+            check_values(&mut zero_by_1_2::Session::new(coins));
+            check_values(&mut zero_by_1_3_4::Session::new(coins));
+        }
+        check_values(&mut tic_tac_toe::Session::new(3, 3, 3));
+        // O wins at once by completing the top row, and every move on the
+        // bottom row lets X complete the middle one
+        let mut game = tic_tac_toe::Session::from_position("OO./XX./... O", 3).unwrap();
+        check_values(&mut game);
+        let mut seen = HashMap::new();
+        solve(&mut game, &mut seen);
+        let values = move_values(&mut game, &seen);
+        assert_eq!(game.encode_move(values[0].0), "c1");
+        assert!(values[0].1 == Outcome::Win(1));
+        assert_eq!(values.iter().filter(|(_, out)| *out == Outcome::Loss(2)).count(), 3);
+        let mut over = tic_tac_toe::Session::from_position("OOO/XX./... X", 3).unwrap();
+        assert!(move_values(&mut over, &seen).is_empty());
+    }
+}
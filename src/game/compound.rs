@@ -0,0 +1,461 @@
+use super::{Game, Outcome};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use bimap::BiMap;
+use uuid::Uuid;
+
+
+pub const DISJUNCTIVE_NAME: &str = "Disjunctive compound";
+pub const DISJUNCTIVE_DESCRIPTION: &str =
+"A disjunctive compound (or sum) is played on several component games at
+once. On each turn, the player to move picks one of the components which
+have not ended yet and makes a move in it. The compound ends when every
+component has ended.";
+
+pub const CONJUNCTIVE_NAME: &str = "Conjunctive compound";
+pub const CONJUNCTIVE_DESCRIPTION: &str =
+"A conjunctive compound is played on several component games at once. On
+each turn, the player to move must make a move in every single component,
+and the compound ends as soon as any one of the components ends.";
+
+pub const SELECTIVE_NAME: &str = "Selective compound";
+pub const SELECTIVE_DESCRIPTION: &str =
+"A selective compound is played on several component games at once. On each
+turn, the player to move picks any non-empty subset of the components which
+have not ended yet and makes a move in each of them. The compound ends when
+every component has ended.";
+
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Disjunctive,
+    Conjunctive,
+    Selective
+}
+
+
+// Components share a single player to move, so it only makes sense to use
+// impartial games (or games which track turns by themselves) as components.
+// The three kinds of compound only differ in which moves they allow and in
+// when they end, so they share everything else.
+pub struct Compound {
+    mode: Mode,
+    components: Vec<Box<dyn Game>>,
+    moves: RefCell<BiMap<Uuid, Vec<Option<Uuid>>>>,
+    states: RefCell<HashMap<Vec<i32>, i32>>,
+    stack: Vec<Vec<Option<Uuid>>>
+}
+
+impl Compound {
+    pub fn new(mode: Mode, components: Vec<Box<dyn Game>>) -> Self {
+        if components.is_empty() {
+            panic!("Compound game with no components.");
+        }
+        Compound {
+            mode,
+            components,
+            moves: RefCell::new(BiMap::new()),
+            states: RefCell::new(HashMap::new()),
+            stack: Vec::new()
+        }
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    pub fn components(&self) -> &Vec<Box<dyn Game>> {
+        &self.components
+    }
+}
+
+impl Game for Compound {
+    fn play(&mut self, mv: Uuid) {
+        let parts = component_moves(&self.moves, mv);
+        play_parts(&mut self.components, &parts);
+        self.stack.push(parts);
+    }
+
+    fn undo(&mut self) {
+        let parts = self.stack.pop().expect("No move to pop!");
+        undo_parts(&mut self.components, &parts);
+    }
+
+    fn possible_moves(&self) -> Vec<Uuid> {
+        let combinations = match self.mode {
+            Mode::Disjunctive => disjunctive_moves(&self.components),
+            Mode::Conjunctive => {
+                if self.outcome().is_some() {
+                    return vec![]
+                }
+                conjunctive_moves(&self.components)
+            },
+            Mode::Selective => selective_moves(&self.components)
+        };
+        combinations
+            .into_iter()
+            .map(|parts| compound_move(&self.moves, parts))
+            .collect()
+    }
+
+    fn history(&self) -> Vec<Uuid> {
+        self.stack
+            .iter()
+            .map(|parts| compound_move(&self.moves, parts.clone()))
+            .collect()
+    }
+
+    fn state(&self) -> i32 {
+        number_states(&self.states, &self.components)
+    }
+
+    // Conjunctive compounds end as soon as any component does, and the other
+    // two only once every component has.
+    fn outcome(&self) -> Option<Outcome> {
+        let ended: Vec<Outcome> = self.components
+            .iter()
+            .filter_map(|c| c.outcome())
+            .collect();
+        if self.mode != Mode::Conjunctive && ended.len() < self.components.len() {
+            return None
+        }
+        worst_outcome(ended)
+    }
+
+    fn encode_move(&self, mv: Uuid) -> String {
+        encode_parts(&self.components, &component_moves(&self.moves, mv))
+    }
+
+    fn decode_move(&self, text: &str) -> Option<Uuid> {
+        let parts = decode_parts(&self.components, text)?;
+        self.possible_moves()
+            .into_iter()
+            .find(|id| component_moves(&self.moves, *id) == parts)
+    }
+
+    fn render(&self) -> String {
+        render_components(&self.components)
+    }
+
+    fn position(&self) -> String {
+        position_components(&self.components)
+    }
+}
+
+
+/* HELPER FUNCTIONS */
+
+// Exactly one component moves, and every other one sits the turn out.
+fn disjunctive_moves(components: &[Box<dyn Game>]) -> Vec<Vec<Option<Uuid>>> {
+    let mut result = Vec::new();
+    for (i, component) in components.iter().enumerate() {
+        if component.outcome().is_some() {
+            continue
+        }
+        for mv in component.possible_moves() {
+            let mut parts = vec![None; components.len()];
+            parts[i] = Some(mv);
+            result.push(parts);
+        }
+    }
+    result
+}
+
+// Every component moves at once.
+fn conjunctive_moves(components: &[Box<dyn Game>]) -> Vec<Vec<Option<Uuid>>> {
+    let mut combinations: Vec<Vec<Option<Uuid>>> = vec![vec![]];
+    for component in components.iter() {
+        let mut extended = Vec::new();
+        for partial in combinations.iter() {
+            for mv in component.possible_moves() {
+                let mut next = partial.clone();
+                next.push(Some(mv));
+                extended.push(next);
+            }
+        }
+        combinations = extended;
+    }
+    combinations
+}
+
+// Each component either sits this turn out (None) or contributes one of its
+// moves, as long as at least one of them moves. Components which have ended
+// always sit out.
+fn selective_moves(components: &[Box<dyn Game>]) -> Vec<Vec<Option<Uuid>>> {
+    let mut combinations: Vec<Vec<Option<Uuid>>> = vec![vec![]];
+    for component in components.iter() {
+        let mut extended = Vec::new();
+        let ended = component.outcome().is_some();
+        for partial in combinations.iter() {
+            let mut skip = partial.clone();
+            skip.push(None);
+            extended.push(skip);
+            if ended {
+                continue
+            }
+            for mv in component.possible_moves() {
+                let mut next = partial.clone();
+                next.push(Some(mv));
+                extended.push(next);
+            }
+        }
+        combinations = extended;
+    }
+    combinations
+        .into_iter()
+        .filter(|parts| parts.iter().any(|p| p.is_some()))
+        .collect()
+}
+
+fn compound_move(
+    moves: &RefCell<BiMap<Uuid, Vec<Option<Uuid>>>>,
+    parts: Vec<Option<Uuid>>
+) -> Uuid {
+    if let Some(id) = moves.borrow().get_by_right(&parts) {
+        return *id
+    }
+    let id = Uuid::new_v4();
+    moves.borrow_mut().insert(id, parts);
+    id
+}
+
+fn component_moves(
+    moves: &RefCell<BiMap<Uuid, Vec<Option<Uuid>>>>,
+    mv: Uuid
+) -> Vec<Option<Uuid>> {
+    moves.borrow()
+        .get_by_left(&mv)
+        .expect("Could not find move.")
+        .clone()
+}
+
+fn play_parts(components: &mut [Box<dyn Game>], parts: &[Option<Uuid>]) {
+    for (component, part) in components.iter_mut().zip(parts) {
+        if let Some(mv) = part {
+            component.play(*mv);
+        }
+    }
+}
+
+fn undo_parts(components: &mut [Box<dyn Game>], parts: &[Option<Uuid>]) {
+    for (component, part) in components.iter_mut().zip(parts) {
+        if part.is_some() {
+            component.undo();
+        }
+    }
+}
+
+// Compound moves are written as the moves made in each component, joined
+// by plus signs, with a dash for components which sit the turn out.
+fn encode_parts(components: &[Box<dyn Game>], parts: &[Option<Uuid>]) -> String {
+    let encoded: Vec<String> = components
+        .iter()
+        .zip(parts)
+        .map(|(component, part)| match part {
+            Some(mv) => component.encode_move(*mv),
+            None => "-".to_string()
+        })
+        .collect();
+    encoded.join("+")
+}
+
+fn decode_parts(components: &[Box<dyn Game>], text: &str) -> Option<Vec<Option<Uuid>>> {
+    let pieces: Vec<&str> = text.trim().split('+').collect();
+    if pieces.len() != components.len() {
+        return None
+    }
+    let mut parts = Vec::new();
+    for (component, piece) in components.iter().zip(pieces) {
+        if piece.trim() == "-" {
+            parts.push(None);
+        } else {
+            parts.push(Some(component.decode_move(piece)?));
+        }
+    }
+    Some(parts)
+}
+
+fn render_components(components: &[Box<dyn Game>]) -> String {
+    let rendered: Vec<String> = components
+        .iter()
+        .enumerate()
+        .map(|(i, component)| format!("Component {}:\n{}", i + 1, component.render()))
+        .collect();
+    rendered.join("\n\n")
+}
+
+// Component positions joined by "+", in the same order as their moves.
+fn position_components(components: &[Box<dyn Game>]) -> String {
+    let positions: Vec<String> = components.iter().map(|c| c.position()).collect();
+    positions.join(" + ")
+}
+
+// Combinations of component states are numbered in the order they are first
+// seen, as there is no bound on the range of values each component might use
+// to encode its own state. Numbers only mean something within one compound.
+fn number_states(states: &RefCell<HashMap<Vec<i32>, i32>>, components: &[Box<dyn Game>]) -> i32 {
+    let key: Vec<i32> = components.iter().map(|c| c.state()).collect();
+    let mut states = states.borrow_mut();
+    let next = states.len() as i32;
+    *states.entry(key).or_insert(next)
+}
+
+// If several components end at once, the player to move gets the worst of
+// their outcomes (a loss beats a tie, and a tie beats a win).
+fn worst_outcome(ended: Vec<Outcome>) -> Option<Outcome> {
+    let mut result = None;
+    for out in ended {
+        result = match (result, out) {
+            (None, _) => Some(out),
+            (Some(Outcome::Loss(_)), _) => result,
+            (_, Outcome::Loss(_)) => Some(out),
+            (Some(Outcome::Tie(_)), _) => result,
+            (_, Outcome::Tie(_)) => Some(out),
+            _ => result
+        };
+    }
+    result
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::misere::Misere;
+    use crate::game::{solve, zero_by_1_2, zero_by_1_3_4};
+    use std::collections::HashSet;
+
+    fn value(game: &mut dyn Game) -> Outcome {
+        match game.outcome() {
+            Some(out) => out,
+            None => solve(game, &mut HashMap::new())
+        }
+    }
+
+    fn pair(a: i32, b: i32) -> Vec<Box<dyn Game>> {
+        vec![
+            Box::new(zero_by_1_2::Session::new(a)),
+            Box::new(zero_by_1_3_4::Session::new(b))
+        ]
+    }
+
+    fn remoteness(out: Outcome) -> i32 {
+        match out {
+            Outcome::Win(rem) | Outcome::Loss(rem) | Outcome::Tie(rem) => rem
+        }
+    }
+
+    // Grundy values of subtracting 1 or 2, and of subtracting 1, 3 or 4
+    fn grundy_1_2(coins: i32) -> i32 {
+        coins % 3
+    }
+
+    fn grundy_1_3_4(coins: i32) -> i32 {
+        [0, 1, 0, 1, 2, 3, 2][(coins % 7) as usize]
+    }
+
+    #[test]
+    fn disjunctive_sums_follow_sprague_grundy() {
+        for a in 0..9 {
+            for b in 0..9 {
+                let out = value(&mut Compound::new(Mode::Disjunctive, pair(a, b)));
+                let first_wins = grundy_1_2(a) ^ grundy_1_3_4(b) != 0;
+                assert_eq!(matches!(out, Outcome::Win(_)), first_wins, "{} + {}", a, b);
+            }
+        }
+    }
+
+    // A selective compound is lost for the player to move exactly when every
+    // component is lost for them on its own.
+    #[test]
+    fn selective_compounds_are_lost_when_every_component_is() {
+        for a in 0..9 {
+            for b in 0..9 {
+                let out = value(&mut Compound::new(Mode::Selective, pair(a, b)));
+                let lost = grundy_1_2(a) == 0 && grundy_1_3_4(b) == 0;
+                assert_eq!(matches!(out, Outcome::Loss(_)), lost, "{} + {}", a, b);
+            }
+        }
+    }
+
+    // Smith's theorem: a conjunctive compound is decided by the component
+    // which ends soonest, and lasts as long as it does.
+    #[test]
+    fn conjunctive_compounds_follow_the_shortest_component() {
+        for a in 0..9 {
+            for b in 0..9 {
+                let out = value(&mut Compound::new(Mode::Conjunctive, pair(a, b)));
+                let shortest = [
+                    value(&mut zero_by_1_2::Session::new(a)),
+                    value(&mut zero_by_1_3_4::Session::new(b))
+                ]
+                .into_iter()
+                .min_by_key(|out| remoteness(*out))
+                .unwrap();
+                assert_eq!(remoteness(out), remoteness(shortest), "{} + {}", a, b);
+                assert_eq!(
+                    matches!(out, Outcome::Win(_)),
+                    matches!(shortest, Outcome::Win(_)),
+                    "{} + {}", a, b
+                );
+            }
+        }
+    }
+
+    // The same holds under misère play, with the misère components.
+    #[test]
+    fn misere_conjunctive_compounds_follow_the_shortest_component() {
+        for a in 0..9 {
+            for b in 0..9 {
+                let out = value(&mut Misere::new(Compound::new(Mode::Conjunctive, pair(a, b))));
+                let shortest = [
+                    value(&mut Misere::new(zero_by_1_2::Session::new(a))),
+                    value(&mut Misere::new(zero_by_1_3_4::Session::new(b)))
+                ]
+                .into_iter()
+                .min_by_key(|out| remoteness(*out))
+                .unwrap();
+                assert_eq!(remoteness(out), remoteness(shortest), "{} + {}", a, b);
+                assert_eq!(
+                    matches!(out, Outcome::Win(_)),
+                    matches!(shortest, Outcome::Win(_)),
+                    "{} + {}", a, b
+                );
+            }
+        }
+    }
+
+    // Walks every position reachable from the current one, checking that
+    // states and positions go together one to one.
+    fn check_states(game: &mut dyn Game, states: &mut HashMap<i32, String>) {
+        let position = game.position();
+        match states.insert(game.state(), position.clone()) {
+            Some(other) => assert_eq!(other, position),
+            None => {
+                for mv in game.possible_moves() {
+                    game.play(mv);
+                    check_states(game, states);
+                    game.undo();
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn different_component_states_get_different_states() {
+        let mut states = HashMap::new();
+        check_states(&mut Compound::new(Mode::Disjunctive, pair(6, 6)), &mut states);
+        let positions: HashSet<&String> = states.values().collect();
+        assert_eq!(positions.len(), states.len());
+        assert_eq!(states.len(), 7 * 7);
+    }
+
+    #[test]
+    fn selective_compounds_skip_ended_components() {
+        let mut game = Compound::new(Mode::Selective, pair(0, 2));
+        for mv in game.possible_moves() {
+            assert!(game.encode_move(mv).starts_with("-+"));
+        }
+        assert!(matches!(value(&mut game), Outcome::Loss(2)));
+    }
+}
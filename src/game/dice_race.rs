@@ -0,0 +1,207 @@
+use super::stochastic::{Moves, Player, Stochastic};
+use bimap::BiMap;
+use uuid::Uuid;
+
+
+pub const GAME_NAME: &str = "Dice race";
+pub const GAME_DESCRIPTION: &str =
+"Dice race is played by two players on a track with N squares. On their
+turn, a player chooses to roll either one die or two dice. With one die, they
+move forward as many squares as the die shows. With two dice, they move
+forward by the sum of both dice, unless any of them shows a one, in which
+case they only move forward a single square. The first player to reach the
+end of the track wins.";
+
+
+#[derive(Eq, PartialEq, Hash, Clone, Copy)]
+pub enum Move {
+    RollOne,
+    RollTwo,
+    Advance(i32)
+}
+
+
+pub struct Session {
+    goal: i32,
+    positions: [i32; 2],
+    rolling: Option<Move>,
+    moves: BiMap<Uuid, Move>,
+    stack: Vec<Move>
+}
+
+impl Session {
+    pub fn new(goal: i32) -> Self {
+        if goal < 1 {
+            panic!("Non-positive track length.");
+        }
+        let mut moves: BiMap<Uuid, Move> = BiMap::new();
+        moves.insert(Uuid::new_v4(), Move::RollOne);
+        moves.insert(Uuid::new_v4(), Move::RollTwo);
+        for n in 1..=12 {
+            moves.insert(Uuid::new_v4(), Move::Advance(n));
+        }
+        Session {
+            goal,
+            positions: [0, 0],
+            rolling: None,
+            moves,
+            stack: Vec::new()
+        }
+    }
+
+    pub fn position(&self, player: Player) -> i32 {
+        self.positions[Self::index(player)].min(self.goal)
+    }
+
+    pub fn move_uuid(&self, mv: Move) -> Uuid {
+        *self.moves.get_by_right(&mv).expect("Invalid move.")
+    }
+
+    fn index(player: Player) -> usize {
+        match player {
+            Player::First => 0,
+            Player::Second => 1
+        }
+    }
+
+    // Probability of moving forward by each amount with the chosen roll.
+    fn distribution(roll: Move) -> Vec<(i32, f64)> {
+        match roll {
+            Move::RollOne => (1..=6).map(|n| (n, 1.0 / 6.0)).collect(),
+            Move::RollTwo => {
+                let mut counts = [0; 13];
+                for a in 1..=6 {
+                    for b in 1..=6 {
+                        if a == 1 || b == 1 {
+                            counts[1] += 1;
+                        } else {
+                            counts[a + b] += 1;
+                        }
+                    }
+                }
+                (1..=12)
+                    .filter(|&n| counts[n] > 0)
+                    .map(|n| (n as i32, counts[n] as f64 / 36.0))
+                    .collect()
+            },
+            Move::Advance(_) => panic!("Not a roll.")
+        }
+    }
+}
+
+impl Stochastic for Session {
+    fn play(&mut self, mv: Uuid) {
+        let mv = *self.moves.get_by_left(&mv).expect("Error finding move.");
+        match (mv, self.rolling) {
+            (Move::Advance(n), Some(_)) => {
+                self.positions[Self::index(self.turn())] += n;
+                self.rolling = None;
+            },
+            (Move::RollOne, None) | (Move::RollTwo, None) => {
+                self.rolling = Some(mv);
+            },
+            _ => panic!("Illegal move!")
+        }
+        self.stack.push(mv);
+    }
+
+    fn undo(&mut self) {
+        match self.stack.pop().expect("Expected move, found nothing.") {
+            Move::Advance(n) => {
+                self.rolling = self.stack.last().copied();
+                self.positions[Self::index(self.turn())] -= n;
+            },
+            _ => self.rolling = None
+        }
+    }
+
+    // Positions past the end of the track are all the same.
+    fn state(&self) -> i32 {
+        let rolling = match self.rolling {
+            None => 0,
+            Some(Move::RollOne) => 1,
+            Some(_) => 2
+        };
+        let turn = Self::index(self.turn()) as i32;
+        let first = self.position(Player::First);
+        let second = self.position(Player::Second);
+        (((first * (self.goal + 1)) + second) * 2 + turn) * 3 + rolling
+    }
+
+    // Each turn is made of a roll choice followed by its advance.
+    fn turn(&self) -> Player {
+        let turns = self.stack
+            .iter()
+            .filter(|mv| matches!(mv, Move::Advance(_)))
+            .count();
+        if turns.is_multiple_of(2) {
+            Player::First
+        } else {
+            Player::Second
+        }
+    }
+
+    fn payoff(&self) -> Option<f64> {
+        if self.position(Player::First) >= self.goal {
+            Some(1.0)
+        } else if self.position(Player::Second) >= self.goal {
+            Some(0.0)
+        } else {
+            None
+        }
+    }
+
+    fn possible_moves(&self) -> Moves {
+        match self.rolling {
+            None => Moves::Choice(vec![
+                self.move_uuid(Move::RollOne),
+                self.move_uuid(Move::RollTwo)
+            ]),
+            Some(roll) => Moves::Chance(
+                Self::distribution(roll)
+                    .into_iter()
+                    .map(|(n, p)| (self.move_uuid(Move::Advance(n)), p))
+                    .collect()
+            )
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::stochastic::{best_move, expectimax};
+    use std::collections::HashMap;
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-9, "{} != {}", a, b);
+    }
+
+    // On a track of two squares, one die gets there at once unless it shows
+    // a one (5/6), while two dice only do without any ones (25/36). After a
+    // one, the other player is left needing two squares with the same
+    // choice, so the first player wins with 5/6 + 1/6 * 1/6 = 31/36.
+    #[test]
+    fn short_track_prefers_one_die() {
+        let mut game = Session::new(2);
+        let mut seen = HashMap::new();
+        assert_close(expectimax(&mut game, &mut seen), 31.0 / 36.0);
+        assert!(best_move(&mut game, &mut seen) == Some(game.move_uuid(Move::RollOne)));
+    }
+
+    // Needing four squares against an opponent one square from the end, the
+    // first player has to get there this turn. One die does half the time,
+    // and two dice whenever neither shows a one (25/36).
+    #[test]
+    fn long_shot_prefers_two_dice() {
+        let mut game = Session::new(5);
+        for mv in [Move::RollOne, Move::Advance(1), Move::RollOne, Move::Advance(4)] {
+            game.play(game.move_uuid(mv));
+        }
+        assert_eq!(game.turn(), Player::First);
+        let mut seen = HashMap::new();
+        assert_close(expectimax(&mut game, &mut seen), 25.0 / 36.0);
+        assert!(best_move(&mut game, &mut seen) == Some(game.move_uuid(Move::RollTwo)));
+    }
+}
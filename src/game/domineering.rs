@@ -0,0 +1,321 @@
+use super::partizan::Partizan;
+use super::{Game, Outcome, encode_coordinate, decode_coordinate, column_header};
+use super::{split_position, join_position};
+use bimap::BiMap;
+use uuid::Uuid;
+
+
+pub const GAME_NAME: &str = "Domineering";
+pub const GAME_DESCRIPTION: &str =
+"Domineering is played on a rectangular grid, where two players take turns
+placing dominoes. Left places them vertically, covering two cells on top of
+each other, and Right places them horizontally, covering two cells next to
+each other. Left goes first, and the player who cannot place a domino on
+their turn loses.";
+
+
+#[derive(Eq, PartialEq, Hash, Clone, Copy)]
+pub enum Move {
+    Vertical(Place),
+    Horizontal(Place)
+}
+
+
+// Top (or leftmost) cell covered by a domino.
+#[derive(Eq, PartialEq, Hash, Clone, Copy)]
+pub struct Place {
+    i: i32,
+    j: i32
+}
+
+pub struct Session {
+    cells: Vec<Vec<bool>>,
+    height: i32,
+    width: i32,
+    moves: BiMap<Uuid, Move>,
+    stack: Vec<Move>,
+    // Dominoes already on the board when the session was set up
+    offset: usize
+}
+
+impl Session {
+    pub fn new(height: i32, width: i32) -> Self {
+        if height < 1 || width < 1 || height * width > 31 {
+            panic!("Board must have between 1 and 31 cells.");
+        }
+        Session {
+            cells: vec![vec![false; width as usize]; height as usize],
+            height,
+            width,
+            moves: Self::map_possible_moves(height, width),
+            stack: Vec::new(),
+            offset: 0
+        }
+    }
+
+    // Sets up a position written as in position(), like "#.../#.../..../.... R",
+    // with one row per slash from top to bottom, "#" for covered cells and
+    // "." for empty ones. Left moves first, so the side to move (which can
+    // be left out) must agree with the number of dominoes on the board.
+    pub fn from_position(text: &str) -> Result<Self, String> {
+        let (rows, side) = split_position(text)?;
+        let (height, width) = (rows.len() as i32, rows[0].len() as i32);
+        if height * width > 31 {
+            return Err("The board can have at most 31 cells.".to_string())
+        }
+        let mut session = Session::new(height, width);
+        let mut covered: usize = 0;
+        for (i, row) in rows.iter().enumerate() {
+            for (j, cell) in row.iter().enumerate() {
+                session.cells[i][j] = match cell {
+                    '#' => true,
+                    '.' => false,
+                    other => return Err(format!("Unknown cell '{}' in the position.", other))
+                };
+                if session.cells[i][j] {
+                    covered += 1;
+                }
+            }
+        }
+        if !covered.is_multiple_of(2) {
+            return Err("Dominoes cover an even number of cells.".to_string())
+        }
+        session.offset = covered / 2;
+        let to_move = if session.offset.is_multiple_of(2) { "L" } else { "R" };
+        if side.is_some_and(|s| !s.eq_ignore_ascii_case(to_move)) {
+            return Err(format!("It is {}'s turn in this position.", to_move))
+        }
+        Ok(session)
+    }
+
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    pub fn print(&self) {
+        for row in self.cells.iter() {
+            for cell in row {
+                if *cell {
+                    print!("# ");
+                } else {
+                    print!(". ");
+                }
+            }
+            println!();
+        }
+    }
+
+    fn map_possible_moves(height: i32, width: i32) -> BiMap<Uuid, Move> {
+        let mut moves: BiMap<Uuid, Move> = BiMap::new();
+        for i in 0..height {
+            for j in 0..width {
+                let place = Place {i, j};
+                if i + 1 < height {
+                    moves.insert(Uuid::new_v4(), Move::Vertical(place));
+                }
+                if j + 1 < width {
+                    moves.insert(Uuid::new_v4(), Move::Horizontal(place));
+                }
+            }
+        }
+        moves
+    }
+
+    fn rows(&self) -> Vec<String> {
+        self.cells
+            .iter()
+            .map(|row| row.iter().map(|cell| if *cell { '#' } else { '.' }).collect())
+            .collect()
+    }
+
+    fn side_to_move(&self) -> &'static str {
+        if (self.offset + self.stack.len()).is_multiple_of(2) {
+            "L"
+        } else {
+            "R"
+        }
+    }
+
+    fn covered(mv: Move) -> [(usize, usize); 2] {
+        match mv {
+            Move::Vertical(p) => [
+                (p.i as usize, p.j as usize),
+                (p.i as usize + 1, p.j as usize)
+            ],
+            Move::Horizontal(p) => [
+                (p.i as usize, p.j as usize),
+                (p.i as usize, p.j as usize + 1)
+            ]
+        }
+    }
+
+    fn available(&self, vertical: bool) -> Vec<Uuid> {
+        let mut result = Vec::new();
+        for (id, mv) in self.moves.iter() {
+            if matches!(mv, Move::Vertical(_)) != vertical {
+                continue;
+            }
+            if Self::covered(*mv).iter().all(|&(i, j)| !self.cells[i][j]) {
+                result.push(*id);
+            }
+        }
+        result
+    }
+}
+
+impl Game for Session {
+    fn play(&mut self, mv: Uuid) {
+        let mv = *self.moves.get_by_left(&mv).expect("Could not find move.");
+        for (i, j) in Self::covered(mv) {
+            if self.cells[i][j] {
+                panic!("Attempted illegal move.");
+            }
+            self.cells[i][j] = true;
+        }
+        self.stack.push(mv);
+    }
+
+    fn undo(&mut self) {
+        let mv = self.stack.pop().expect("No move to pop!");
+        for (i, j) in Self::covered(mv) {
+            self.cells[i][j] = false;
+        }
+    }
+
+    fn possible_moves(&self) -> Vec<Uuid> {
+        if (self.offset + self.stack.len()).is_multiple_of(2) {
+            self.left_moves()
+        } else {
+            self.right_moves()
+        }
+    }
+
+    fn history(&self) -> Vec<Uuid> {
+        self.stack
+            .iter()
+            .map(|mv| *self.moves.get_by_right(mv).expect("Could not find move."))
+            .collect()
+    }
+
+    // Only the covered cells are recorded, since the amount of dominoes on
+    // the board tells whose turn it is.
+    fn state(&self) -> i32 {
+        let mut hash = 0;
+        let mut counter = 0;
+        for i in 0..self.height {
+            for j in 0..self.width {
+                if self.cells[i as usize][j as usize] {
+                    hash += 1 << counter;
+                }
+                counter += 1;
+            }
+        }
+        hash
+    }
+
+    fn outcome(&self) -> Option<Outcome> {
+        if self.possible_moves().is_empty() {
+            Some(Outcome::Loss(0))
+        } else {
+            None
+        }
+    }
+
+    fn encode_move(&self, mv: Uuid) -> String {
+        match *self.moves.get_by_left(&mv).expect("Could not find move.") {
+            Move::Vertical(place) | Move::Horizontal(place) => {
+                encode_coordinate(place.i, place.j)
+            }
+        }
+    }
+
+    // Moves are written as the coordinate of the top (for Left) or leftmost
+    // (for Right) cell covered by the domino.
+    fn decode_move(&self, text: &str) -> Option<Uuid> {
+        let (i, j) = decode_coordinate(text)?;
+        self.possible_moves()
+            .into_iter()
+            .find(|id| match self.moves.get_by_left(id) {
+                Some(Move::Vertical(place)) | Some(Move::Horizontal(place)) => {
+                    place.i == i && place.j == j
+                },
+                None => false
+            })
+    }
+
+    fn render(&self) -> String {
+        let mut lines = vec![column_header(self.width)];
+        for (i, row) in self.cells.iter().enumerate() {
+            let mut line = format!("{:>2} ", i + 1);
+            for cell in row {
+                line.push_str(if *cell { " #" } else { " ." });
+            }
+            lines.push(line);
+        }
+        lines.join("\n")
+    }
+
+    fn position(&self) -> String {
+        join_position(self.rows(), self.side_to_move())
+    }
+
+    // Mirroring the board either way leaves the game the same, but turning
+    // it would swap which dominoes are Left's and which are Right's.
+    fn canonical_position(&self) -> String {
+        let rows = self.rows();
+        let flipped: Vec<String> = rows.iter().rev().cloned().collect();
+        let mirror = |rows: &[String]| -> Vec<String> {
+            rows.iter().map(|row| row.chars().rev().collect()).collect()
+        };
+        [mirror(&rows), mirror(&flipped), flipped, rows]
+            .into_iter()
+            .map(|rows| join_position(rows, self.side_to_move()))
+            .min()
+            .expect("There is always at least one position.")
+    }
+}
+
+impl Partizan for Session {
+    fn left_moves(&self) -> Vec<Uuid> {
+        self.available(true)
+    }
+
+    fn right_moves(&self) -> Vec<Uuid> {
+        self.available(false)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::partizan::{value, Dyadic, Value};
+    use std::collections::HashMap;
+
+    fn board_value(position: &str) -> Value {
+        let mut session = Session::from_position(position).unwrap();
+        value(&mut session, &mut HashMap::new())
+    }
+
+    #[test]
+    fn strips_are_integers() {
+        assert_eq!(board_value("./."), Value::integer(1));
+        assert_eq!(board_value(".."), Value::integer(-1));
+        assert_eq!(board_value("./././."), Value::integer(2));
+        assert_eq!(board_value("...."), Value::integer(-2));
+    }
+
+    #[test]
+    fn small_boards_have_their_known_values() {
+        let plus_minus_one = Value::new(vec![Value::integer(1)], vec![Value::integer(-1)]);
+        assert_eq!(board_value("../.."), plus_minus_one);
+        assert_eq!(board_value(".../.../..."), plus_minus_one);
+        assert_eq!(board_value("##./..."), Value::dyadic(Dyadic::new(-1, 1)));
+        assert_eq!(board_value("#.#/..."), Value::star());
+        assert_eq!(board_value("../..").to_string(), "±1");
+    }
+}
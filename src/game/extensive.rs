@@ -0,0 +1,402 @@
+use super::stochastic::{Moves, Player};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+
+// Extensive-form games are two-player zero-sum games with chance positions
+// and imperfect information. The player to move cannot tell apart positions
+// in the same information set, so these must offer the exact same moves (in
+// the same order). Information sets of the two players must not overlap, and
+// payoffs are from the perspective of the first player.
+pub trait Extensive {
+    fn play(&mut self, mv: Uuid);
+    fn undo(&mut self);
+    fn turn(&self) -> Player;
+    fn infoset(&self) -> i32;
+    fn payoff(&self) -> Option<f64>;
+    fn possible_moves(&self) -> Moves;
+}
+
+
+// A behavioural strategy, mapping information sets to the probability of
+// choosing each of their moves.
+pub type Strategy = HashMap<i32, Vec<(Uuid, f64)>>;
+
+
+// Counterfactual regret minimization. Each iteration walks the whole game
+// tree, accumulating how much each player regrets not having chosen each
+// move in each of their information sets. The average strategy over all
+// iterations converges to a Nash equilibrium.
+pub struct Cfr {
+    tree: Vec<Vertex>,
+    moves: HashMap<i32, Vec<Uuid>>,
+    regrets: HashMap<i32, Vec<f64>>,
+    strategy_sum: HashMap<i32, Vec<f64>>,
+    iterations: usize
+}
+
+impl Cfr {
+    pub fn new(game: &mut dyn Extensive) -> Self {
+        let mut tree = Vec::new();
+        let mut moves = HashMap::new();
+        expand(game, &mut tree, &mut moves);
+        let regrets = moves
+            .iter()
+            .map(|(i, m)| (*i, vec![0.0; m.len()]))
+            .collect();
+        let strategy_sum = moves
+            .iter()
+            .map(|(i, m)| (*i, vec![0.0; m.len()]))
+            .collect();
+        Cfr {
+            tree,
+            moves,
+            regrets,
+            strategy_sum,
+            iterations: 0
+        }
+    }
+
+    pub fn iterate(&mut self, iterations: usize) {
+        for _ in 0..iterations {
+            self.walk(0, [1.0, 1.0], 1.0);
+            self.iterations += 1;
+        }
+    }
+
+    pub fn iterations(&self) -> usize {
+        self.iterations
+    }
+
+    // The strategy each player has played on average, which is what
+    // approaches an equilibrium.
+    pub fn average_strategy(&self) -> Strategy {
+        let mut result = HashMap::new();
+        for (infoset, sums) in self.strategy_sum.iter() {
+            let probs = normalize(sums);
+            result.insert(*infoset, self.moves[infoset]
+                .iter()
+                .copied()
+                .zip(probs)
+                .collect());
+        }
+        result
+    }
+
+    // Expected payoff of the first player when both follow the strategy.
+    pub fn value(&self, strategy: &Strategy) -> f64 {
+        self.expected(0, strategy)
+    }
+
+    // How much the players could gain on average by deviating from the
+    // strategy, which is zero exactly at a Nash equilibrium.
+    pub fn exploitability(&self, strategy: &Strategy) -> f64 {
+        let first = self.best_response(Player::First, strategy);
+        let second = self.best_response(Player::Second, strategy);
+        (first + second) / 2.0
+    }
+
+    /* HELPER METHODS */
+
+    // Returns the value of the node for the first player, given the reach
+    // probabilities of each player and of chance.
+    fn walk(&mut self, node: usize, reach: [f64; 2], chance: f64) -> f64 {
+        match &self.tree[node] {
+            Vertex::Terminal(val) => *val,
+            Vertex::Chance(children) => {
+                let children = children.clone();
+                let mut expected = 0.0;
+                for (child, prob) in children {
+                    expected += prob * self.walk(child, reach, chance * prob);
+                }
+                expected
+            },
+            Vertex::Decision(player, infoset, children) => {
+                let (player, infoset, children) = (*player, *infoset, children.clone());
+                let me = index(player);
+                let sigma = normalize_positive(&self.regrets[&infoset]);
+                let mut values = Vec::new();
+                let mut expected = 0.0;
+                for (child, prob) in children.iter().zip(sigma.iter()) {
+                    let mut next = reach;
+                    next[me] *= prob;
+                    let val = self.walk(*child, next, chance);
+                    values.push(val);
+                    expected += prob * val;
+                }
+                let sign = if me == 0 { 1.0 } else { -1.0 };
+                let counterfactual = reach[1 - me] * chance;
+                let regrets = self.regrets.get_mut(&infoset).unwrap();
+                for (regret, val) in regrets.iter_mut().zip(values) {
+                    *regret += counterfactual * sign * (val - expected);
+                }
+                let sums = self.strategy_sum.get_mut(&infoset).unwrap();
+                for (sum, prob) in sums.iter_mut().zip(sigma) {
+                    *sum += reach[me] * prob;
+                }
+                expected
+            }
+        }
+    }
+
+    fn expected(&self, node: usize, strategy: &Strategy) -> f64 {
+        match &self.tree[node] {
+            Vertex::Terminal(val) => *val,
+            Vertex::Chance(children) => children
+                .iter()
+                .map(|(child, prob)| prob * self.expected(*child, strategy))
+                .sum(),
+            Vertex::Decision(_, infoset, children) => children
+                .iter()
+                .zip(probabilities(strategy, *infoset, children.len()))
+                .map(|(child, prob)| prob * self.expected(*child, strategy))
+                .sum()
+        }
+    }
+
+    // Value the player gets by best responding to the other player's part
+    // of the strategy, from the perspective of the responding player.
+    fn best_response(&self, player: Player, strategy: &Strategy) -> f64 {
+        let mut response = Response {
+            responder: player,
+            members: HashMap::new(),
+            chosen: HashMap::new(),
+            cache: vec![None; self.tree.len()]
+        };
+        self.collect(0, strategy, 1.0, &mut response);
+        let val = self.respond(0, strategy, &mut response);
+        match player {
+            Player::First => val,
+            Player::Second => -val
+        }
+    }
+
+    // Finds the nodes in each of the responder's information sets, along
+    // with the probability that chance and the other player reach them.
+    fn collect(&self, node: usize, strategy: &Strategy, reach: f64, response: &mut Response) {
+        match &self.tree[node] {
+            Vertex::Terminal(_) => (),
+            Vertex::Chance(children) => {
+                for (child, prob) in children {
+                    self.collect(*child, strategy, reach * prob, response);
+                }
+            },
+            Vertex::Decision(player, infoset, children) => {
+                if *player == response.responder {
+                    response.members.entry(*infoset).or_default().push((node, reach));
+                    for child in children {
+                        self.collect(*child, strategy, reach, response);
+                    }
+                } else {
+                    let probs = probabilities(strategy, *infoset, children.len());
+                    for (child, prob) in children.iter().zip(probs) {
+                        self.collect(*child, strategy, reach * prob, response);
+                    }
+                }
+            }
+        }
+    }
+
+    // Value for the first player when the responder always picks, in each
+    // information set, the move that is best across all of its nodes.
+    fn respond(&self, node: usize, strategy: &Strategy, response: &mut Response) -> f64 {
+        if let Some(val) = response.cache[node] {
+            return val
+        }
+        let val = match &self.tree[node] {
+            Vertex::Terminal(val) => *val,
+            Vertex::Chance(children) => {
+                let mut expected = 0.0;
+                for (child, prob) in children {
+                    expected += prob * self.respond(*child, strategy, response);
+                }
+                expected
+            },
+            Vertex::Decision(player, infoset, children) => {
+                if *player != response.responder {
+                    let probs = probabilities(strategy, *infoset, children.len());
+                    let mut expected = 0.0;
+                    for (child, prob) in children.iter().zip(probs) {
+                        expected += prob * self.respond(*child, strategy, response);
+                    }
+                    expected
+                } else {
+                    let best = self.choose(*player, *infoset, strategy, response);
+                    self.respond(children[best], strategy, response)
+                }
+            }
+        };
+        response.cache[node] = Some(val);
+        val
+    }
+
+    // Picks the move in the information set which does best on average over
+    // all of the nodes in it, weighted by how likely they are to be reached.
+    fn choose(
+        &self,
+        player: Player,
+        infoset: i32,
+        strategy: &Strategy,
+        response: &mut Response
+    ) -> usize {
+        if let Some(best) = response.chosen.get(&infoset) {
+            return *best
+        }
+        let sign = if player == Player::First { 1.0 } else { -1.0 };
+        let members = response.members[&infoset].clone();
+        let mut totals: Vec<f64> = Vec::new();
+        for (member, reach) in members {
+            if let Vertex::Decision(_, _, options) = &self.tree[member] {
+                totals.resize(options.len(), 0.0);
+                for (a, child) in options.iter().enumerate() {
+                    totals[a] += reach * sign * self.respond(*child, strategy, response);
+                }
+            }
+        }
+        let mut best = 0;
+        for (a, total) in totals.iter().enumerate() {
+            if *total > totals[best] {
+                best = a;
+            }
+        }
+        response.chosen.insert(infoset, best);
+        best
+    }
+}
+
+
+/* HELPER FUNCTIONS */
+
+enum Vertex {
+    Terminal(f64),
+    Chance(Vec<(usize, f64)>),
+    Decision(Player, i32, Vec<usize>)
+}
+
+struct Response {
+    responder: Player,
+    members: HashMap<i32, Vec<(usize, f64)>>,
+    chosen: HashMap<i32, usize>,
+    cache: Vec<Option<f64>>
+}
+
+// Lays out the whole game tree, returning the index of the current position.
+fn expand(
+    game: &mut dyn Extensive,
+    tree: &mut Vec<Vertex>,
+    moves: &mut HashMap<i32, Vec<Uuid>>
+) -> usize {
+    let node = tree.len();
+    if let Some(val) = game.payoff() {
+        tree.push(Vertex::Terminal(val));
+        return node
+    }
+    match game.possible_moves() {
+        Moves::Chance(options) => {
+            tree.push(Vertex::Chance(vec![]));
+            let mut children = Vec::new();
+            for (mv, prob) in options {
+                game.play(mv);
+                children.push((expand(game, tree, moves), prob));
+                game.undo();
+            }
+            tree[node] = Vertex::Chance(children);
+        },
+        Moves::Choice(options) => {
+            let player = game.turn();
+            let infoset = game.infoset();
+            tree.push(Vertex::Decision(player, infoset, vec![]));
+            let mut children = Vec::new();
+            for mv in options.iter() {
+                game.play(*mv);
+                children.push(expand(game, tree, moves));
+                game.undo();
+            }
+            tree[node] = Vertex::Decision(player, infoset, children);
+            moves.insert(infoset, options);
+        }
+    }
+    node
+}
+
+fn index(player: Player) -> usize {
+    match player {
+        Player::First => 0,
+        Player::Second => 1
+    }
+}
+
+fn probabilities(strategy: &Strategy, infoset: i32, count: usize) -> Vec<f64> {
+    match strategy.get(&infoset) {
+        Some(probs) => probs.iter().map(|(_, p)| *p).collect(),
+        None => vec![1.0 / count as f64; count]
+    }
+}
+
+fn normalize(values: &[f64]) -> Vec<f64> {
+    let total: f64 = values.iter().sum();
+    if total > 0.0 {
+        values.iter().map(|v| v / total).collect()
+    } else {
+        vec![1.0 / values.len() as f64; values.len()]
+    }
+}
+
+// Regret matching: play moves in proportion to their positive regret.
+fn normalize_positive(regrets: &[f64]) -> Vec<f64> {
+    let positive: Vec<f64> = regrets.iter().map(|r| r.max(0.0)).collect();
+    normalize(&positive)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::kuhn_poker::{Move, Session};
+
+    // Information sets of Kuhn poker for a card (jack, queen or king) and
+    // the betting so far, as numbered by the game
+    const OPENING: i32 = 3;
+    const AFTER_PASS: i32 = 12;
+    const AFTER_BET: i32 = 15;
+    const AFTER_PASS_BET: i32 = 42;
+
+    // Kuhn's equilibrium where the first player never bluffs: the value is
+    // -1/18 for the first player.
+    fn equilibrium(game: &Session) -> Strategy {
+        let (pass, bet) = (game.move_uuid(Move::Pass), game.move_uuid(Move::Bet));
+        let mut strategy = Strategy::new();
+        let mut set = |infoset: i32, card: i32, betting: f64| {
+            strategy.insert(infoset + card, vec![(pass, 1.0 - betting), (bet, betting)]);
+        };
+        for (card, opening, calling) in [(0, 0.0, 0.0), (1, 0.0, 1.0 / 3.0), (2, 0.0, 1.0)] {
+            set(OPENING, card, opening);
+            set(AFTER_PASS_BET, card, calling);
+        }
+        for (card, bluffing, calling) in [(0, 1.0 / 3.0, 0.0), (1, 0.0, 1.0 / 3.0), (2, 1.0, 1.0)] {
+            set(AFTER_PASS, card, bluffing);
+            set(AFTER_BET, card, calling);
+        }
+        strategy
+    }
+
+    #[test]
+    fn exploitability_of_known_strategies() {
+        let mut game = Session::new();
+        let cfr = Cfr::new(&mut game);
+        let strategy = equilibrium(&game);
+        assert!((cfr.value(&strategy) + 1.0 / 18.0).abs() < 1e-9);
+        assert!(cfr.exploitability(&strategy).abs() < 1e-9);
+        // Playing every move with the same probability
+        assert!((cfr.exploitability(&Strategy::new()) - 11.0 / 24.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn cfr_finds_the_value_of_kuhn_poker() {
+        let mut cfr = Cfr::new(&mut Session::new());
+        cfr.iterate(10000);
+        let strategy = cfr.average_strategy();
+        assert!((cfr.value(&strategy) + 1.0 / 18.0).abs() < 1e-3);
+        assert!(cfr.exploitability(&strategy) < 1e-2);
+    }
+}
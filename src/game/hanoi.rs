@@ -0,0 +1,109 @@
+use super::puzzle::Puzzle;
+use bimap::BiMap;
+use uuid::Uuid;
+
+
+pub const GAME_NAME: &str = "Tower of Hanoi";
+pub const GAME_DESCRIPTION: &str =
+"The Tower of Hanoi starts with N discs of decreasing size stacked on the
+first of three pegs. A move takes the top disc of one peg and places it on
+top of another peg, but never on top of a smaller disc. The puzzle is solved
+once all of the discs are stacked on the last peg.";
+
+
+#[derive(Eq, PartialEq, Hash, Clone, Copy)]
+pub struct Move {
+    from: usize,
+    to: usize
+}
+
+
+pub struct Session {
+    pegs: [Vec<i32>; 3],
+    discs: i32,
+    moves: BiMap<Uuid, Move>,
+    stack: Vec<Move>
+}
+
+impl Session {
+    pub fn new(discs: i32) -> Self {
+        if !(1..=19).contains(&discs) {
+            panic!("Number of discs must be between 1 and 19.");
+        }
+        let mut moves: BiMap<Uuid, Move> = BiMap::new();
+        for from in 0..3 {
+            for to in 0..3 {
+                if from != to {
+                    moves.insert(Uuid::new_v4(), Move { from, to });
+                }
+            }
+        }
+        Session {
+            pegs: [(0..discs).rev().collect(), Vec::new(), Vec::new()],
+            discs,
+            moves,
+            stack: Vec::new()
+        }
+    }
+
+    pub fn print(&self) {
+        for (i, peg) in self.pegs.iter().enumerate() {
+            let discs: Vec<String> = peg.iter().map(|d| (d + 1).to_string()).collect();
+            println!("{} | {}", i + 1, discs.join(" "));
+        }
+    }
+
+    fn legal(&self, mv: &Move) -> bool {
+        match (self.pegs[mv.from].last(), self.pegs[mv.to].last()) {
+            (None, _) => false,
+            (Some(_), None) => true,
+            (Some(disc), Some(top)) => disc < top
+        }
+    }
+}
+
+impl Puzzle for Session {
+    fn play(&mut self, mv: Uuid) {
+        let mv = *self.moves.get_by_left(&mv).expect("Error finding move.");
+        if !self.legal(&mv) {
+            panic!("Illegal move!");
+        }
+        let disc = self.pegs[mv.from].pop().unwrap();
+        self.pegs[mv.to].push(disc);
+        self.stack.push(mv);
+    }
+
+    fn undo(&mut self) {
+        let mv = self.stack.pop().expect("Expected move, found nothing.");
+        let disc = self.pegs[mv.to].pop().unwrap();
+        self.pegs[mv.from].push(disc);
+    }
+
+    // Which peg each disc is on, in base 3.
+    fn state(&self) -> i32 {
+        let mut hash = 0;
+        for (i, peg) in self.pegs.iter().enumerate() {
+            for disc in peg {
+                hash += i as i32 * 3_i32.pow(*disc as u32);
+            }
+        }
+        hash
+    }
+
+    fn solved(&self) -> bool {
+        self.pegs[2].len() == self.discs as usize
+    }
+
+    // Every disc which is not on the last peg has to move at least once.
+    fn heuristic(&self) -> i32 {
+        (self.pegs[0].len() + self.pegs[1].len()) as i32
+    }
+
+    fn possible_moves(&self) -> Vec<Uuid> {
+        self.moves
+            .iter()
+            .filter(|(_, mv)| self.legal(mv))
+            .map(|(id, _)| *id)
+            .collect()
+    }
+}
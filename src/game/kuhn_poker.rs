@@ -0,0 +1,135 @@
+use super::extensive::Extensive;
+use super::stochastic::{Moves, Player};
+use bimap::BiMap;
+use uuid::Uuid;
+
+
+pub const GAME_NAME: &str = "Kuhn poker";
+pub const GAME_DESCRIPTION: &str =
+"Kuhn poker is played with a deck of three cards (a jack, a queen, and a
+king). Both players ante one chip and are dealt a card each, which they keep
+hidden. The first player may check or bet one chip. After a check, the second
+player may check (ending the hand) or bet. After a bet, the other player may
+fold (giving up the pot) or call. If nobody folds, the higher card wins.";
+
+
+#[derive(Eq, PartialEq, Hash, Clone, Copy)]
+pub enum Move {
+    Deal(i32, i32),
+    Pass,
+    Bet
+}
+
+
+pub struct Session {
+    moves: BiMap<Uuid, Move>,
+    stack: Vec<Move>
+}
+
+impl Session {
+    pub fn new() -> Self {
+        let mut moves: BiMap<Uuid, Move> = BiMap::new();
+        for first in 0..3 {
+            for second in 0..3 {
+                if first != second {
+                    moves.insert(Uuid::new_v4(), Move::Deal(first, second));
+                }
+            }
+        }
+        moves.insert(Uuid::new_v4(), Move::Pass);
+        moves.insert(Uuid::new_v4(), Move::Bet);
+        Session {
+            moves,
+            stack: Vec::new()
+        }
+    }
+
+    pub fn move_uuid(&self, mv: Move) -> Uuid {
+        *self.moves.get_by_right(&mv).expect("Invalid move.")
+    }
+
+    fn cards(&self) -> Option<(i32, i32)> {
+        match self.stack.first() {
+            Some(Move::Deal(first, second)) => Some((*first, *second)),
+            _ => None
+        }
+    }
+
+    fn betting(&self) -> &[Move] {
+        if self.stack.is_empty() {
+            &[]
+        } else {
+            &self.stack[1..]
+        }
+    }
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Extensive for Session {
+    fn play(&mut self, mv: Uuid) {
+        let mv = *self.moves.get_by_left(&mv).expect("Error finding move.");
+        self.stack.push(mv);
+    }
+
+    fn undo(&mut self) {
+        self.stack.pop().expect("Expected move, found nothing.");
+    }
+
+    fn turn(&self) -> Player {
+        if self.betting().len().is_multiple_of(2) {
+            Player::First
+        } else {
+            Player::Second
+        }
+    }
+
+    // The card of the player to move, along with the betting so far.
+    fn infoset(&self) -> i32 {
+        let (first, second) = self.cards().expect("No cards dealt.");
+        let card = match self.turn() {
+            Player::First => first,
+            Player::Second => second
+        };
+        let mut history = 1;
+        for mv in self.betting() {
+            history = history * 3 + match mv {
+                Move::Pass => 1,
+                _ => 2
+            };
+        }
+        history * 3 + card
+    }
+
+    fn payoff(&self) -> Option<f64> {
+        let (first, second) = self.cards()?;
+        let showdown = if first > second { 1.0 } else { -1.0 };
+        match self.betting() {
+            [Move::Pass, Move::Pass] => Some(showdown),
+            [Move::Bet, Move::Pass] => Some(1.0),
+            [Move::Pass, Move::Bet, Move::Pass] => Some(-1.0),
+            [Move::Bet, Move::Bet] | [Move::Pass, Move::Bet, Move::Bet] => Some(2.0 * showdown),
+            _ => None
+        }
+    }
+
+    fn possible_moves(&self) -> Moves {
+        if self.stack.is_empty() {
+            let deals: Vec<(Uuid, f64)> = self.moves
+                .iter()
+                .filter(|(_, mv)| matches!(mv, Move::Deal(_, _)))
+                .map(|(id, _)| (*id, 1.0 / 6.0))
+                .collect();
+            Moves::Chance(deals)
+        } else {
+            Moves::Choice(vec![
+                self.move_uuid(Move::Pass),
+                self.move_uuid(Move::Bet)
+            ])
+        }
+    }
+}
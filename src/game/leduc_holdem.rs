@@ -0,0 +1,230 @@
+use super::extensive::Extensive;
+use super::stochastic::{Moves, Player};
+use bimap::BiMap;
+use uuid::Uuid;
+
+
+pub const GAME_NAME: &str = "Leduc hold'em";
+pub const GAME_DESCRIPTION: &str =
+"Leduc hold'em is played with a deck of six cards (two jacks, two queens,
+and two kings). Both players ante one chip and are dealt a hidden card each.
+There are two betting rounds, with bets of two chips in the first round and
+four chips in the second, and at most two bets per round. Between rounds, a
+public card is revealed. If nobody folds, a player whose card pairs the public
+card wins, and otherwise the higher card wins (or the pot is split).";
+
+
+#[derive(Eq, PartialEq, Hash, Clone, Copy)]
+pub enum Move {
+    Deal(i32),
+    Fold,
+    Call,
+    Raise
+}
+
+
+pub struct Session {
+    moves: BiMap<Uuid, Move>,
+    stack: Vec<Move>
+}
+
+// Everything that can be told from the moves made so far.
+struct Summary {
+    private: [Option<i32>; 2],
+    public: Option<i32>,
+    round: usize,
+    actions: usize,
+    raises: usize,
+    contributions: [i32; 2],
+    turn: Player,
+    folded: Option<Player>,
+    round_over: bool
+}
+
+impl Session {
+    pub fn new() -> Self {
+        let mut moves: BiMap<Uuid, Move> = BiMap::new();
+        for rank in 0..3 {
+            moves.insert(Uuid::new_v4(), Move::Deal(rank));
+        }
+        moves.insert(Uuid::new_v4(), Move::Fold);
+        moves.insert(Uuid::new_v4(), Move::Call);
+        moves.insert(Uuid::new_v4(), Move::Raise);
+        Session {
+            moves,
+            stack: Vec::new()
+        }
+    }
+
+    pub fn move_uuid(&self, mv: Move) -> Uuid {
+        *self.moves.get_by_right(&mv).expect("Invalid move.")
+    }
+
+    fn summary(&self) -> Summary {
+        let mut summary = Summary {
+            private: [None, None],
+            public: None,
+            round: 0,
+            actions: 0,
+            raises: 0,
+            contributions: [1, 1],
+            turn: Player::First,
+            folded: None,
+            round_over: false
+        };
+        for mv in self.stack.iter() {
+            let me = index(summary.turn);
+            match mv {
+                Move::Deal(rank) => {
+                    if summary.private[0].is_none() {
+                        summary.private[0] = Some(*rank);
+                    } else if summary.private[1].is_none() {
+                        summary.private[1] = Some(*rank);
+                    } else {
+                        summary.public = Some(*rank);
+                        summary.round = 1;
+                        summary.actions = 0;
+                        summary.raises = 0;
+                        summary.turn = Player::First;
+                        summary.round_over = false;
+                    }
+                    continue;
+                },
+                Move::Fold => {
+                    summary.folded = Some(summary.turn);
+                },
+                Move::Call => {
+                    summary.contributions[me] = summary.contributions[1 - me];
+                    if summary.actions > 0 {
+                        summary.round_over = true;
+                    }
+                },
+                Move::Raise => {
+                    let bet = if summary.round == 0 { 2 } else { 4 };
+                    summary.contributions[me] = summary.contributions[1 - me] + bet;
+                    summary.raises += 1;
+                }
+            }
+            summary.actions += 1;
+            summary.turn = other(summary.turn);
+        }
+        summary
+    }
+
+    // Chance of dealing each rank, given the cards which are already out.
+    fn deals(&self, summary: &Summary) -> Vec<(Uuid, f64)> {
+        let mut left = [2, 2, 2];
+        for card in summary.private.iter().flatten() {
+            left[*card as usize] -= 1;
+        }
+        let total: i32 = left.iter().sum();
+        (0..3)
+            .filter(|rank| left[*rank as usize] > 0)
+            .map(|rank| {
+                let prob = left[rank as usize] as f64 / total as f64;
+                (self.move_uuid(Move::Deal(rank)), prob)
+            })
+            .collect()
+    }
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Extensive for Session {
+    fn play(&mut self, mv: Uuid) {
+        let mv = *self.moves.get_by_left(&mv).expect("Error finding move.");
+        self.stack.push(mv);
+    }
+
+    fn undo(&mut self) {
+        self.stack.pop().expect("Expected move, found nothing.");
+    }
+
+    fn turn(&self) -> Player {
+        self.summary().turn
+    }
+
+    // The private card of the player to move, the public card (if any), and
+    // the betting so far.
+    fn infoset(&self) -> i32 {
+        let summary = self.summary();
+        let card = summary.private[index(summary.turn)].expect("No card dealt.");
+        let public = summary.public.map(|c| c + 1).unwrap_or(0);
+        let mut history = 1;
+        for mv in self.stack.iter().skip(2) {
+            history = history * 4 + match mv {
+                Move::Fold => 0,
+                Move::Call => 1,
+                Move::Raise => 2,
+                Move::Deal(_) => 3
+            };
+        }
+        (history * 4 + public) * 3 + card
+    }
+
+    fn payoff(&self) -> Option<f64> {
+        let summary = self.summary();
+        match summary.folded {
+            Some(Player::First) => return Some(-summary.contributions[0] as f64),
+            Some(Player::Second) => return Some(summary.contributions[1] as f64),
+            None => ()
+        }
+        if summary.round < 1 || !summary.round_over {
+            return None
+        }
+        let public = summary.public?;
+        let first = summary.private[0]?;
+        let second = summary.private[1]?;
+        let pot = summary.contributions[0] as f64;
+        if first == public {
+            Some(pot)
+        } else if second == public {
+            Some(-pot)
+        } else if first > second {
+            Some(pot)
+        } else if second > first {
+            Some(-pot)
+        } else {
+            Some(0.0)
+        }
+    }
+
+    // Folding is only allowed when facing a bet.
+    fn possible_moves(&self) -> Moves {
+        let summary = self.summary();
+        if summary.private[1].is_none() || summary.round_over {
+            return Moves::Chance(self.deals(&summary))
+        }
+        let me = index(summary.turn);
+        let mut moves = Vec::new();
+        if summary.contributions[me] < summary.contributions[1 - me] {
+            moves.push(self.move_uuid(Move::Fold));
+        }
+        moves.push(self.move_uuid(Move::Call));
+        if summary.raises < 2 {
+            moves.push(self.move_uuid(Move::Raise));
+        }
+        Moves::Choice(moves)
+    }
+}
+
+
+/* HELPER FUNCTIONS */
+
+fn index(player: Player) -> usize {
+    match player {
+        Player::First => 0,
+        Player::Second => 1
+    }
+}
+
+fn other(player: Player) -> Player {
+    match player {
+        Player::First => Player::Second,
+        Player::Second => Player::First
+    }
+}
@@ -0,0 +1,113 @@
+use super::{Game, Outcome};
+use uuid::Uuid;
+
+
+pub const GAME_NAME: &str = "Misère";
+pub const GAME_DESCRIPTION: &str =
+"The misère variant of a game is played exactly like the original, except
+that its terminal outcomes are flipped around. Whoever would have won the
+original game loses the misère variant, and vice versa. Ties stay ties.";
+
+
+pub struct Misere<G: Game> {
+    game: G
+}
+
+impl<G: Game> Misere<G> {
+    pub fn new(game: G) -> Self {
+        Misere { game }
+    }
+
+    pub fn inner(&self) -> &G {
+        &self.game
+    }
+
+    pub fn into_inner(self) -> G {
+        self.game
+    }
+}
+
+impl<G: Game> Game for Misere<G> {
+    fn play(&mut self, mv: Uuid) {
+        self.game.play(mv);
+    }
+
+    fn undo(&mut self) {
+        self.game.undo();
+    }
+
+    fn possible_moves(&self) -> Vec<Uuid> {
+        self.game.possible_moves()
+    }
+
+    fn history(&self) -> Vec<Uuid> {
+        self.game.history()
+    }
+
+    fn state(&self) -> i32 {
+        self.game.state()
+    }
+
+    fn outcome(&self) -> Option<Outcome> {
+        match self.game.outcome() {
+            Some(Outcome::Win(rem)) => Some(Outcome::Loss(rem)),
+            Some(Outcome::Loss(rem)) => Some(Outcome::Win(rem)),
+            other => other
+        }
+    }
+
+    fn encode_move(&self, mv: Uuid) -> String {
+        self.game.encode_move(mv)
+    }
+
+    fn decode_move(&self, text: &str) -> Option<Uuid> {
+        self.game.decode_move(text)
+    }
+
+    fn render(&self) -> String {
+        self.game.render()
+    }
+
+    fn position(&self) -> String {
+        self.game.position()
+    }
+
+    fn canonical_position(&self) -> String {
+        self.game.canonical_position()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{solve, tic_tac_toe, zero_by_1_2};
+    use std::collections::HashMap;
+
+    // Whoever takes the last coin loses, so leaving one coin for the other
+    // player wins, and the losing counts are one more than a multiple of 3.
+    #[test]
+    fn misere_zero_by_1_2_is_lost_at_one_more_than_a_multiple_of_three() {
+        for coins in 0..16 {
+            let mut game = Misere::new(zero_by_1_2::Session::new(coins));
+            let out = match game.outcome() {
+                Some(out) => out,
+                None => solve(&mut game, &mut HashMap::new())
+            };
+            assert_eq!(matches!(out, Outcome::Loss(_)), coins % 3 == 1, "{} coins", coins);
+        }
+    }
+
+    // O has just completed a line, which loses for X to move in the original
+    // game and wins for X in the misère one.
+    #[test]
+    fn misere_tic_tac_toe_flips_completed_lines() {
+        let original = tic_tac_toe::Session::from_position("OOO/XX./... X", 3).unwrap();
+        assert!(matches!(original.outcome(), Some(Outcome::Loss(0))));
+        let misere = Misere::new(original);
+        assert!(matches!(misere.outcome(), Some(Outcome::Win(0))));
+
+        let drawn = tic_tac_toe::Session::from_position("OXO/OXX/XOO X", 3).unwrap();
+        assert!(matches!(Misere::new(drawn).outcome(), Some(Outcome::Tie(0))));
+    }
+}
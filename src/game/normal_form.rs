@@ -0,0 +1,524 @@
+use std::fmt;
+
+
+// Tolerance used when comparing floating point quantities.
+const EPSILON: f64 = 1e-9;
+
+
+// A simultaneous-move game, where each player picks one of their strategies
+// without knowing what the others picked, and then every player receives a
+// payoff according to the strategy profile that was played.
+#[derive(Clone)]
+pub struct NormalForm {
+    shape: Vec<usize>,
+    payoffs: Vec<Vec<f64>>
+}
+
+
+// A mixed strategy for each player, along with the payoff each of them
+// expects to get when everyone plays them.
+#[derive(Clone)]
+pub struct Equilibrium {
+    pub strategies: Vec<Vec<f64>>,
+    pub payoffs: Vec<f64>
+}
+
+impl NormalForm {
+    pub fn new(shape: Vec<usize>) -> Self {
+        if shape.is_empty() || shape.contains(&0) {
+            panic!("Every player needs at least one strategy.");
+        }
+        let profiles = shape.iter().product();
+        let players = shape.len();
+        NormalForm {
+            shape,
+            payoffs: vec![vec![0.0; players]; profiles]
+        }
+    }
+
+    // Two-player game where a[i][j] and b[i][j] are the payoffs of the row
+    // and column players when they play strategies i and j.
+    pub fn bimatrix(a: Vec<Vec<f64>>, b: Vec<Vec<f64>>) -> Self {
+        let rows = a.len();
+        let columns = a.first().map(|r| r.len()).unwrap_or(0);
+        let mut game = Self::new(vec![rows, columns]);
+        for i in 0..rows {
+            for j in 0..columns {
+                game.set_payoff(&[i, j], vec![a[i][j], b[i][j]]);
+            }
+        }
+        game
+    }
+
+    // Two-player game where the column player loses what the row player wins.
+    pub fn zero_sum(a: Vec<Vec<f64>>) -> Self {
+        let b = a.iter()
+            .map(|row| row.iter().map(|v| -v).collect())
+            .collect();
+        Self::bimatrix(a, b)
+    }
+
+    pub fn players(&self) -> usize {
+        self.shape.len()
+    }
+
+    pub fn strategies(&self, player: usize) -> usize {
+        self.shape[player]
+    }
+
+    pub fn payoff(&self, profile: &[usize]) -> &Vec<f64> {
+        &self.payoffs[self.index(profile)]
+    }
+
+    pub fn set_payoff(&mut self, profile: &[usize], payoffs: Vec<f64>) {
+        if payoffs.len() != self.players() {
+            panic!("Expected one payoff per player.");
+        }
+        let index = self.index(profile);
+        self.payoffs[index] = payoffs;
+    }
+
+    // Payoff each player expects when everyone plays the mixed strategies.
+    pub fn expected_payoffs(&self, strategies: &[Vec<f64>]) -> Vec<f64> {
+        let mut result = vec![0.0; self.players()];
+        for profile in self.profiles() {
+            let prob: f64 = profile
+                .iter()
+                .enumerate()
+                .map(|(p, s)| strategies[p][*s])
+                .product();
+            if prob == 0.0 {
+                continue;
+            }
+            for (total, val) in result.iter_mut().zip(self.payoff(&profile)) {
+                *total += prob * val;
+            }
+        }
+        result
+    }
+
+    // Solves a two-player zero-sum game (using the payoffs of the row player)
+    // by linear programming. Every equilibrium of such a game has the same
+    // value, so the one that is found is as good as any other.
+    pub fn solve_zero_sum(&self) -> Equilibrium {
+        if self.players() != 2 {
+            panic!("Zero-sum solving needs exactly two players.");
+        }
+        let (rows, columns) = (self.shape[0], self.shape[1]);
+        let min = self.payoffs.iter().map(|p| p[0]).fold(f64::INFINITY, f64::min);
+        let shift = 1.0 - min;
+        // Column player: maximize sum(w) subject to A w <= 1 and w >= 0
+        let mut constraints = Vec::new();
+        for i in 0..rows {
+            let row: Vec<f64> = (0..columns)
+                .map(|j| self.payoff(&[i, j])[0] + shift)
+                .collect();
+            constraints.push(row);
+        }
+        let (w, u) = simplex(&constraints, &vec![1.0; columns]);
+        let total: f64 = w.iter().sum();
+        let value = 1.0 / total - shift;
+        let column: Vec<f64> = w.iter().map(|x| x / total).collect();
+        let dual: f64 = u.iter().sum();
+        let row: Vec<f64> = u.iter().map(|x| x / dual).collect();
+        let strategies = vec![row, column];
+        let payoffs = self.expected_payoffs(&strategies);
+        debug_assert!((payoffs[0] - value).abs() < 1e-6);
+        Equilibrium { strategies, payoffs }
+    }
+
+    // Finds an equilibrium of a two-player game by following the path of
+    // complementary pivots that starts by dropping the given label. Labels
+    // 0 to m - 1 are the row player's strategies, and the rest belong to the
+    // column player.
+    pub fn lemke_howson(&self, label: usize) -> Equilibrium {
+        if self.players() != 2 {
+            panic!("Lemke-Howson needs exactly two players.");
+        }
+        let (rows, columns) = (self.shape[0], self.shape[1]);
+        if label >= rows + columns {
+            panic!("Label out of range.");
+        }
+        let min = self.payoffs
+            .iter()
+            .flatten()
+            .fold(f64::INFINITY, |a, b| a.min(*b));
+        let shift = 1.0 - min;
+        // Row player's tableau, with slacks r (labels 0..m) and y (labels
+        // m..m+n), for A y + r = 1
+        let mut first = Tableau::new(rows, rows + columns, 0);
+        for i in 0..rows {
+            first.set(i, i, 1.0);
+            for j in 0..columns {
+                first.set(i, rows + j, self.payoff(&[i, j])[0] + shift);
+            }
+        }
+        // Column player's tableau, with x (labels 0..m) and slacks s (labels
+        // m..m+n), for B^T x + s = 1
+        let mut second = Tableau::new(columns, rows + columns, rows);
+        for j in 0..columns {
+            second.set(j, rows + j, 1.0);
+            for i in 0..rows {
+                second.set(j, i, self.payoff(&[i, j])[1] + shift);
+            }
+        }
+        let mut entering = label;
+        let mut in_second = label < rows;
+        loop {
+            let leaving = if in_second {
+                second.pivot(entering)
+            } else {
+                first.pivot(entering)
+            };
+            if leaving == label {
+                break;
+            }
+            entering = leaving;
+            in_second = !in_second;
+        }
+        let mut x = vec![0.0; rows];
+        let mut y = vec![0.0; columns];
+        for (row, basic) in second.basis.iter().enumerate() {
+            if *basic < rows {
+                x[*basic] = second.rhs(row);
+            }
+        }
+        for (row, basic) in first.basis.iter().enumerate() {
+            if *basic >= rows {
+                y[*basic - rows] = first.rhs(row);
+            }
+        }
+        let strategies = vec![normalize(&x), normalize(&y)];
+        let payoffs = self.expected_payoffs(&strategies);
+        Equilibrium { strategies, payoffs }
+    }
+
+    // Runs Lemke-Howson from every initial label, keeping distinct results.
+    pub fn equilibria(&self) -> Vec<Equilibrium> {
+        let mut found: Vec<Equilibrium> = Vec::new();
+        for label in 0..(self.shape[0] + self.shape[1]) {
+            let eq = self.lemke_howson(label);
+            if !found.iter().any(|f| f.same(&eq)) {
+                found.push(eq);
+            }
+        }
+        found
+    }
+
+    // Repeatedly removes strategies which do strictly worse than another
+    // pure strategy of the same player against everything the others might
+    // do. Returns the reduced game along with the original indices of the
+    // strategies that survive for each player.
+    pub fn eliminate_dominated(&self) -> (NormalForm, Vec<Vec<usize>>) {
+        let mut alive: Vec<Vec<usize>> = self.shape
+            .iter()
+            .map(|n| (0..*n).collect())
+            .collect();
+        loop {
+            let mut removed = false;
+            for player in 0..self.players() {
+                let candidates = alive[player].clone();
+                for s in candidates {
+                    let dominated = alive[player]
+                        .iter()
+                        .any(|t| *t != s && self.dominates(player, *t, s, &alive));
+                    if dominated {
+                        alive[player].retain(|x| *x != s);
+                        removed = true;
+                    }
+                }
+            }
+            if !removed {
+                break;
+            }
+        }
+        let mut reduced = NormalForm::new(alive.iter().map(|a| a.len()).collect());
+        for profile in reduced.profiles() {
+            let original: Vec<usize> = profile
+                .iter()
+                .enumerate()
+                .map(|(p, s)| alive[p][*s])
+                .collect();
+            reduced.set_payoff(&profile, self.payoff(&original).clone());
+        }
+        (reduced, alive)
+    }
+
+    /* HELPER METHODS */
+
+    fn index(&self, profile: &[usize]) -> usize {
+        if profile.len() != self.players() {
+            panic!("Expected one strategy per player.");
+        }
+        let mut index = 0;
+        for (s, n) in profile.iter().zip(self.shape.iter()) {
+            if s >= n {
+                panic!("Strategy out of range.");
+            }
+            index = index * n + s;
+        }
+        index
+    }
+
+    fn profiles(&self) -> Vec<Vec<usize>> {
+        let mut result: Vec<Vec<usize>> = vec![vec![]];
+        for n in self.shape.iter() {
+            let mut extended = Vec::new();
+            for partial in result.iter() {
+                for s in 0..*n {
+                    let mut next = partial.clone();
+                    next.push(s);
+                    extended.push(next);
+                }
+            }
+            result = extended;
+        }
+        result
+    }
+
+    // Whether strategy t of the player does strictly better than s against
+    // every combination of the remaining strategies of the others.
+    fn dominates(&self, player: usize, t: usize, s: usize, alive: &[Vec<usize>]) -> bool {
+        let mut others: Vec<Vec<usize>> = vec![vec![]];
+        for (p, strategies) in alive.iter().enumerate() {
+            let mut extended = Vec::new();
+            for partial in others.iter() {
+                if p == player {
+                    let mut next = partial.clone();
+                    next.push(0);
+                    extended.push(next);
+                    continue;
+                }
+                for x in strategies {
+                    let mut next = partial.clone();
+                    next.push(*x);
+                    extended.push(next);
+                }
+            }
+            others = extended;
+        }
+        others.into_iter().all(|mut profile| {
+            profile[player] = t;
+            let better = self.payoff(&profile)[player];
+            profile[player] = s;
+            better > self.payoff(&profile)[player] + EPSILON
+        })
+    }
+}
+
+impl Equilibrium {
+    fn same(&self, other: &Equilibrium) -> bool {
+        self.strategies
+            .iter()
+            .flatten()
+            .zip(other.strategies.iter().flatten())
+            .all(|(a, b)| (a - b).abs() < 1e-6)
+    }
+}
+
+impl fmt::Display for Equilibrium {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (player, (strategy, payoff)) in self.strategies
+            .iter()
+            .zip(self.payoffs.iter())
+            .enumerate() {
+            let probs: Vec<String> = strategy
+                .iter()
+                .map(|p| format!("{:.4}", p))
+                .collect();
+            writeln!(f, "Player {}: ({})\tpayoff {:.4}", player + 1, probs.join(", "), payoff)?;
+        }
+        Ok(())
+    }
+}
+
+
+/* HELPER FUNCTIONS */
+
+// Dense simplex tableau with a basic variable for each constraint row.
+struct Tableau {
+    rows: Vec<Vec<f64>>,
+    basis: Vec<usize>,
+    // Label of the first variable in the starting basis
+    first: usize
+}
+
+impl Tableau {
+    // Each row starts as an equation equal to one, whose basic variable is
+    // the one with label first + row.
+    fn new(rows: usize, variables: usize, first: usize) -> Self {
+        let mut contents = vec![vec![0.0; variables + 1]; rows];
+        for row in contents.iter_mut() {
+            row[variables] = 1.0;
+        }
+        Tableau {
+            rows: contents,
+            basis: (first..first + rows).collect(),
+            first
+        }
+    }
+
+    fn set(&mut self, row: usize, variable: usize, value: f64) {
+        self.rows[row][variable] = value;
+    }
+
+    fn rhs(&self, row: usize) -> f64 {
+        *self.rows[row].last().unwrap()
+    }
+
+    // Brings the variable into the basis, returning the one that leaves.
+    // The leaving variable is chosen by the lexicographic ratio test, which
+    // never cycles, even on degenerate games.
+    fn pivot(&mut self, entering: usize) -> usize {
+        let mut best: Option<usize> = None;
+        for r in 0..self.rows.len() {
+            if self.rows[r][entering] > EPSILON
+                && best.is_none_or(|b| self.lexicographically_smaller(r, b, entering)) {
+                best = Some(r);
+            }
+        }
+        let r = best.expect("Unbounded pivot.");
+        let coefficient = self.rows[r][entering];
+        for v in self.rows[r].iter_mut() {
+            *v /= coefficient;
+        }
+        let pivot_row = self.rows[r].clone();
+        for (i, row) in self.rows.iter_mut().enumerate() {
+            if i != r && row[entering].abs() > 0.0 {
+                let factor = row[entering];
+                for (v, p) in row.iter_mut().zip(pivot_row.iter()) {
+                    *v -= factor * p;
+                }
+            }
+        }
+        std::mem::replace(&mut self.basis[r], entering)
+    }
+
+    // Compares the ratios of two rows, breaking ties with the columns of the
+    // starting basis (scaled the same way) one after another. Those columns
+    // hold the inverse of the basis, so no two rows are ever tied throughout.
+    fn lexicographically_smaller(&self, a: usize, b: usize, entering: usize) -> bool {
+        let columns = std::iter::once(self.rows[0].len() - 1)
+            .chain(self.first..self.first + self.rows.len());
+        for column in columns {
+            let x = self.rows[a][column] / self.rows[a][entering];
+            let y = self.rows[b][column] / self.rows[b][entering];
+            if x < y - EPSILON {
+                return true
+            }
+            if x > y + EPSILON {
+                return false
+            }
+        }
+        false
+    }
+}
+
+// Maximizes c^T w subject to A w <= 1 and w >= 0. Returns the optimal w
+// along with the optimal dual solution u (one per constraint).
+fn simplex(a: &[Vec<f64>], c: &[f64]) -> (Vec<f64>, Vec<f64>) {
+    let (m, n) = (a.len(), c.len());
+    // Variables are w (0..n) followed by slacks (n..n+m)
+    let mut tableau = Tableau::new(m, n + m, n);
+    for (i, row) in a.iter().enumerate() {
+        for (j, v) in row.iter().enumerate() {
+            tableau.set(i, j, *v);
+        }
+        tableau.set(i, n + i, 1.0);
+    }
+    let mut objective: Vec<f64> = c.iter().map(|v| -v).collect();
+    objective.resize(n + m + 1, 0.0);
+    // Bland's rule for the entering variable, which along with the
+    // lexicographic choice of the leaving one avoids cycling
+    while let Some(entering) = (0..n + m).find(|j| objective[*j] < -EPSILON) {
+        tableau.pivot(entering);
+        let row = tableau.basis.iter().position(|b| *b == entering).unwrap();
+        let factor = objective[entering];
+        for (v, p) in objective.iter_mut().zip(tableau.rows[row].iter()) {
+            *v -= factor * p;
+        }
+    }
+    let mut w = vec![0.0; n];
+    for (row, basic) in tableau.basis.iter().enumerate() {
+        if *basic < n {
+            w[*basic] = tableau.rhs(row);
+        }
+    }
+    let u = objective[n..n + m].to_vec();
+    (w, u)
+}
+
+fn normalize(values: &[f64]) -> Vec<f64> {
+    let total: f64 = values.iter().sum();
+    values.iter().map(|v| v / total).collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // No player can gain by switching to any single pure strategy.
+    fn is_equilibrium(game: &NormalForm, eq: &Equilibrium) -> bool {
+        (0..game.players()).all(|player| {
+            (0..game.strategies(player)).all(|s| {
+                let mut strategies = eq.strategies.clone();
+                strategies[player] = (0..game.strategies(player))
+                    .map(|t| if t == s { 1.0 } else { 0.0 })
+                    .collect();
+                game.expected_payoffs(&strategies)[player] <= eq.payoffs[player] + 1e-6
+            })
+        })
+    }
+
+    fn close(a: &[f64], b: &[f64]) -> bool {
+        a.iter().zip(b).all(|(x, y)| (x - y).abs() < 1e-9)
+    }
+
+    #[test]
+    fn zero_sum_games_are_solved_by_linear_programming() {
+        let rock_paper_scissors = NormalForm::zero_sum(vec![
+            vec![0.0, -1.0, 1.0],
+            vec![1.0, 0.0, -1.0],
+            vec![-1.0, 1.0, 0.0]
+        ]);
+        let eq = rock_paper_scissors.solve_zero_sum();
+        assert!(close(&eq.strategies[0], &[1.0 / 3.0; 3]));
+        assert!(close(&eq.strategies[1], &[1.0 / 3.0; 3]));
+        assert!(close(&eq.payoffs, &[0.0, 0.0]));
+        // Neither player has a pure strategy worth playing on its own, and
+        // the row player wins 1/7 on average
+        let eq = NormalForm::zero_sum(vec![vec![3.0, -1.0], vec![-2.0, 1.0]]).solve_zero_sum();
+        assert!(close(&eq.strategies[0], &[3.0 / 7.0, 4.0 / 7.0]));
+        assert!(close(&eq.strategies[1], &[2.0 / 7.0, 5.0 / 7.0]));
+        assert!(close(&eq.payoffs, &[1.0 / 7.0, -1.0 / 7.0]));
+    }
+
+    #[test]
+    fn lemke_howson_handles_degenerate_games() {
+        let games = [
+            // von Stengel's degenerate example, where the first row has two
+            // pure best responses
+            NormalForm::bimatrix(
+                vec![vec![3.0, 3.0], vec![2.0, 5.0], vec![0.0, 6.0]],
+                vec![vec![3.0, 3.0], vec![2.0, 6.0], vec![3.0, 1.0]]
+            ),
+            // Without breaking ties in the ratio test, dropping the third
+            // label pivots around in a circle forever
+            NormalForm::bimatrix(
+                vec![vec![1.0, 2.0, 0.0], vec![2.0, 2.0, 0.0], vec![1.0, 0.0, 0.0]],
+                vec![vec![0.0, 2.0, 1.0], vec![1.0, 0.0, 2.0], vec![1.0, 1.0, 1.0]]
+            ),
+            NormalForm::bimatrix(
+                vec![vec![1.0, 1.0], vec![1.0, 1.0]],
+                vec![vec![1.0, 1.0], vec![1.0, 1.0]]
+            )
+        ];
+        for game in games.iter() {
+            for label in 0..(game.strategies(0) + game.strategies(1)) {
+                let eq = game.lemke_howson(label);
+                assert!(is_equilibrium(game, &eq), "label {}:\n{}", label, eq);
+            }
+        }
+    }
+}
@@ -0,0 +1,551 @@
+use super::Game;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::{Add, Neg, Sub};
+use uuid::Uuid;
+
+
+// A partizan game lets us ask for the moves available to each side, no
+// matter whose turn it currently is. Playing any of those moves through
+// Game::play must be legal at any point.
+pub trait Partizan: Game {
+    fn left_moves(&self) -> Vec<Uuid>;
+    fn right_moves(&self) -> Vec<Uuid>;
+}
+
+
+// Computes the canonical combinatorial game value {L | R} of the position
+// the game is currently in. States must not depend on whose turn it is.
+pub fn value(game: &mut dyn Partizan, seen: &mut HashMap<i32, Value>) -> Value {
+    let mut left = Vec::new();
+    for mv in game.left_moves() {
+        left.push(child_value(game, mv, seen));
+    }
+    let mut right = Vec::new();
+    for mv in game.right_moves() {
+        right.push(child_value(game, mv, seen));
+    }
+    Value::new(left, right)
+}
+
+
+fn child_value(
+    game: &mut dyn Partizan,
+    mv: Uuid,
+    seen: &mut HashMap<i32, Value>
+) -> Value {
+    game.play(mv);
+    let encoded_state = game.state();
+    let out = if let Some(val) = seen.get(&encoded_state) {
+        val.clone()
+    } else {
+        let val = value(game, seen);
+        seen.insert(encoded_state, val.clone());
+        val
+    };
+    game.undo();
+    out
+}
+
+
+/* ---------- GAME VALUES ---------- */
+
+// A game value in canonical form. Since canonical forms are unique (and we
+// keep options sorted), two values are equal exactly when they are equal
+// as games, so structural equality is game equality.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Value {
+    left: Vec<Value>,
+    right: Vec<Value>
+}
+
+impl Value {
+    pub fn new(left: Vec<Value>, right: Vec<Value>) -> Self {
+        Value { left, right }.canonical()
+    }
+
+    pub fn zero() -> Self {
+        Value { left: vec![], right: vec![] }
+    }
+
+    pub fn star() -> Self {
+        Self::nimber(1)
+    }
+
+    pub fn up() -> Self {
+        Value::new(vec![Self::zero()], vec![Self::star()])
+    }
+
+    pub fn down() -> Self {
+        -Self::up()
+    }
+
+    pub fn integer(n: i64) -> Self {
+        Self::dyadic(Dyadic::integer(n))
+    }
+
+    pub fn dyadic(x: Dyadic) -> Self {
+        if x.exp == 0 {
+            match x.num.cmp(&0) {
+                Ordering::Equal => Self::zero(),
+                Ordering::Greater => Value {
+                    left: vec![Self::integer(x.num - 1)],
+                    right: vec![]
+                },
+                Ordering::Less => Value {
+                    left: vec![],
+                    right: vec![Self::integer(x.num + 1)]
+                }
+            }
+        } else {
+            let step = Dyadic::new(1, x.exp);
+            Value {
+                left: vec![Self::dyadic(x - step)],
+                right: vec![Self::dyadic(x + step)]
+            }
+        }
+    }
+
+    pub fn nimber(n: usize) -> Self {
+        let options: Vec<Value> = (0..n).map(Self::nimber).collect();
+        Value::new(options.clone(), options)
+    }
+
+    pub fn left_options(&self) -> &Vec<Value> {
+        &self.left
+    }
+
+    pub fn right_options(&self) -> &Vec<Value> {
+        &self.right
+    }
+
+    // Returns the dyadic rational this value is equal to, if it is a number.
+    pub fn number(&self) -> Option<Dyadic> {
+        if self.left.len() > 1 || self.right.len() > 1 {
+            return None
+        }
+        let l = match self.left.first() {
+            Some(v) => Some(v.number()?),
+            None => None
+        };
+        let r = match self.right.first() {
+            Some(v) => Some(v.number()?),
+            None => None
+        };
+        if let (Some(a), Some(b)) = (l, r) {
+            if a >= b {
+                return None
+            }
+        }
+        Some(Dyadic::simplest_between(l, r))
+    }
+
+    // Returns n if this value is the nimber *n.
+    pub fn nimber_value(&self) -> Option<usize> {
+        let (x, n) = self.number_nimber()?;
+        if x == Dyadic::integer(0) {
+            Some(n)
+        } else {
+            None
+        }
+    }
+
+    // Returns (x, n) if this value is equal to x + *n for a number x.
+    pub fn number_nimber(&self) -> Option<(Dyadic, usize)> {
+        if let Some(x) = self.number() {
+            return Some((x, 0))
+        }
+        if self.left != self.right {
+            return None
+        }
+        let mut base = None;
+        let mut nims = Vec::new();
+        for option in self.left.iter() {
+            let (x, n) = option.number_nimber()?;
+            if base.is_some() && base != Some(x) {
+                return None
+            }
+            base = Some(x);
+            nims.push(n);
+        }
+        nims.sort();
+        if nims.iter().enumerate().all(|(i, n)| i == *n) {
+            Some((base?, nims.len()))
+        } else {
+            None
+        }
+    }
+
+    // Returns true if Left wins playing first.
+    pub fn left_wins_first(&self) -> bool {
+        !self.leq(&Self::zero())
+    }
+
+    // Returns true if Right wins playing first.
+    pub fn right_wins_first(&self) -> bool {
+        !Self::zero().leq(self)
+    }
+
+    /* HELPER METHODS */
+
+    // G <= H unless there is a G^L with H <= G^L or a H^R with H^R <= G.
+    fn leq(&self, other: &Value) -> bool {
+        !self.left.iter().any(|gl| other.leq(gl))
+            && !other.right.iter().any(|hr| hr.leq(self))
+    }
+
+    fn canonical(mut self) -> Self {
+        loop {
+            let bypassed = self.bypass_reversible();
+            let pruned = self.remove_dominated();
+            if !bypassed && !pruned {
+                break;
+            }
+        }
+        self.left.sort_by(structural);
+        self.right.sort_by(structural);
+        self
+    }
+
+    // Replaces each reversible option with the options of its reversal.
+    fn bypass_reversible(&mut self) -> bool {
+        let mut changed = false;
+        let mut left = Vec::new();
+        for gl in self.left.iter() {
+            if let Some(glr) = gl.right.iter().find(|glr| glr.leq(self)) {
+                left.extend(glr.left.iter().cloned());
+                changed = true;
+            } else {
+                left.push(gl.clone());
+            }
+        }
+        let mut right = Vec::new();
+        for gr in self.right.iter() {
+            if let Some(grl) = gr.left.iter().find(|grl| self.leq(grl)) {
+                right.extend(grl.right.iter().cloned());
+                changed = true;
+            } else {
+                right.push(gr.clone());
+            }
+        }
+        self.left = left;
+        self.right = right;
+        changed
+    }
+
+    // Removes options which are no better for their player than another.
+    fn remove_dominated(&mut self) -> bool {
+        let before = self.left.len() + self.right.len();
+        self.left = undominated(&self.left, |a, b| a.leq(b));
+        self.right = undominated(&self.right, |a, b| b.leq(a));
+        before != self.left.len() + self.right.len()
+    }
+}
+
+impl PartialOrd for Value {
+    fn partial_cmp(&self, other: &Value) -> Option<Ordering> {
+        match (self.leq(other), other.leq(self)) {
+            (true, true) => Some(Ordering::Equal),
+            (true, false) => Some(Ordering::Less),
+            (false, true) => Some(Ordering::Greater),
+            (false, false) => None
+        }
+    }
+}
+
+impl Add for Value {
+    type Output = Value;
+
+    fn add(self, other: Value) -> Value {
+        let mut left = Vec::new();
+        let mut right = Vec::new();
+        for gl in self.left.iter() {
+            left.push(gl.clone() + other.clone());
+        }
+        for hl in other.left.iter() {
+            left.push(self.clone() + hl.clone());
+        }
+        for gr in self.right.iter() {
+            right.push(gr.clone() + other.clone());
+        }
+        for hr in other.right.iter() {
+            right.push(self.clone() + hr.clone());
+        }
+        Value::new(left, right)
+    }
+}
+
+impl Neg for Value {
+    type Output = Value;
+
+    fn neg(self) -> Value {
+        Value::new(
+            self.right.into_iter().map(|v| -v).collect(),
+            self.left.into_iter().map(|v| -v).collect()
+        )
+    }
+}
+
+impl Sub for Value {
+    type Output = Value;
+
+    fn sub(self, other: Value) -> Value {
+        self + (-other)
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some((x, n)) = self.number_nimber() {
+            return match (x == Dyadic::integer(0), n) {
+                (true, 0) => write!(f, "0"),
+                (true, 1) => write!(f, "*"),
+                (true, _) => write!(f, "*{}", n),
+                (false, 0) => write!(f, "{}", x),
+                (false, 1) => write!(f, "{}*", x),
+                (false, _) => write!(f, "{}*{}", x, n)
+            }
+        }
+        for (name, val) in infinitesimals() {
+            if *self == val {
+                return write!(f, "{}", name)
+            }
+        }
+        if let ([l], [r]) = (&self.left[..], &self.right[..]) {
+            if let (Some(a), Some(b)) = (l.number(), r.number()) {
+                let sum = a + b;
+                let mean = Dyadic::new(sum.num, sum.exp + 1);
+                let temp = a - mean;
+                return if mean == Dyadic::integer(0) {
+                    write!(f, "±{}", temp)
+                } else {
+                    write!(f, "{}±{}", mean, temp)
+                }
+            }
+        }
+        let left: Vec<String> = self.left.iter().map(|v| v.to_string()).collect();
+        let right: Vec<String> = self.right.iter().map(|v| v.to_string()).collect();
+        write!(f, "{{{} | {}}}", left.join(", "), right.join(", "))
+    }
+}
+
+
+/* ---------- DYADIC RATIONALS ---------- */
+
+// The number num / 2^exp, always kept in lowest terms.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Dyadic {
+    num: i64,
+    exp: u32
+}
+
+impl Dyadic {
+    pub fn new(num: i64, exp: u32) -> Self {
+        let mut result = Dyadic { num, exp };
+        while result.exp > 0 && result.num % 2 == 0 {
+            result.num /= 2;
+            result.exp -= 1;
+        }
+        result
+    }
+
+    pub fn integer(n: i64) -> Self {
+        Dyadic { num: n, exp: 0 }
+    }
+
+    pub fn numerator(&self) -> i64 {
+        self.num
+    }
+
+    pub fn denominator(&self) -> i64 {
+        1 << self.exp
+    }
+
+    // The simplest number strictly between a and b, where a missing bound
+    // stands for an infinite one.
+    fn simplest_between(a: Option<Dyadic>, b: Option<Dyadic>) -> Dyadic {
+        let zero = Dyadic::integer(0);
+        match (a, b) {
+            (None, None) => zero,
+            (Some(a), None) => {
+                if a < zero { zero } else { Dyadic::integer(a.floor() + 1) }
+            },
+            (None, Some(b)) => {
+                if b > zero { zero } else { Dyadic::integer(b.ceil() - 1) }
+            },
+            (Some(a), Some(b)) => {
+                if a < zero && zero < b {
+                    zero
+                } else if b <= zero {
+                    let neg = |x: Dyadic| Dyadic { num: -x.num, exp: x.exp };
+                    neg(Dyadic::simplest_between(Some(neg(b)), Some(neg(a))))
+                } else {
+                    // Smallest multiple of 1 / 2^exp greater than a, with
+                    // the coarsest grid that fits it below b
+                    let mut exp = 0;
+                    loop {
+                        let num = (a.num << exp).div_euclid(1 << a.exp) + 1;
+                        let candidate = Dyadic::new(num, exp);
+                        if candidate < b {
+                            return candidate
+                        }
+                        exp += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    fn floor(&self) -> i64 {
+        self.num.div_euclid(1 << self.exp)
+    }
+
+    fn ceil(&self) -> i64 {
+        -(-self.num).div_euclid(1 << self.exp)
+    }
+}
+
+impl PartialOrd for Dyadic {
+    fn partial_cmp(&self, other: &Dyadic) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Dyadic {
+    fn cmp(&self, other: &Dyadic) -> Ordering {
+        let exp = self.exp.max(other.exp);
+        let a = self.num * (1 << (exp - self.exp));
+        let b = other.num * (1 << (exp - other.exp));
+        a.cmp(&b)
+    }
+}
+
+impl Add for Dyadic {
+    type Output = Dyadic;
+
+    fn add(self, other: Dyadic) -> Dyadic {
+        let exp = self.exp.max(other.exp);
+        Dyadic::new(
+            self.num * (1 << (exp - self.exp)) + other.num * (1 << (exp - other.exp)),
+            exp
+        )
+    }
+}
+
+impl Sub for Dyadic {
+    type Output = Dyadic;
+
+    fn sub(self, other: Dyadic) -> Dyadic {
+        self + Dyadic { num: -other.num, exp: other.exp }
+    }
+}
+
+impl fmt::Display for Dyadic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.exp == 0 {
+            write!(f, "{}", self.num)
+        } else {
+            write!(f, "{}/{}", self.num, self.denominator())
+        }
+    }
+}
+
+
+/* HELPER FUNCTIONS */
+
+// Keeps the options which are not beaten by (or equal to an earlier) option,
+// where worse(a, b) means that b is at least as good as a.
+fn undominated<F>(options: &[Value], worse: F) -> Vec<Value>
+where
+    F: Fn(&Value, &Value) -> bool
+{
+    let mut result: Vec<Value> = Vec::new();
+    for (i, a) in options.iter().enumerate() {
+        let dominated = options.iter().enumerate().any(|(j, b)| {
+            i != j && worse(a, b) && (!worse(b, a) || j < i)
+        });
+        if !dominated {
+            result.push(a.clone());
+        }
+    }
+    result
+}
+
+// An arbitrary but fixed total order on canonical forms, used to sort them.
+fn structural(a: &Value, b: &Value) -> Ordering {
+    a.left.len().cmp(&b.left.len())
+        .then(a.right.len().cmp(&b.right.len()))
+        .then_with(|| {
+            a.left.iter()
+                .chain(a.right.iter())
+                .zip(b.left.iter().chain(b.right.iter()))
+                .map(|(x, y)| structural(x, y))
+                .find(|o| *o != Ordering::Equal)
+                .unwrap_or(Ordering::Equal)
+        })
+}
+
+fn infinitesimals() -> Vec<(&'static str, Value)> {
+    let up = Value::up();
+    let star = Value::star();
+    vec![
+        ("↑", up.clone()),
+        ("↓", -up.clone()),
+        ("↑*", up.clone() + star.clone()),
+        ("↓*", -up.clone() + star.clone()),
+        ("⇑", up.clone() + up.clone()),
+        ("⇓", -(up.clone() + up.clone())),
+        ("⇑*", up.clone() + up.clone() + star.clone()),
+        ("⇓*", -(up.clone() + up)  + star)
+    ]
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn half() -> Value {
+        Value::dyadic(Dyadic::new(1, 1))
+    }
+
+    #[test]
+    fn canonical_forms_drop_dominated_and_reversible_options() {
+        let (zero, star) = (Value::zero(), Value::star());
+        assert_eq!(Value::new(vec![Value::integer(-1), zero.clone()], vec![]), Value::integer(1));
+        assert_eq!(Value::new(vec![zero.clone()], vec![zero.clone(), Value::integer(1)]), star);
+        assert_eq!(Value::new(vec![star.clone()], vec![star]), zero);
+    }
+
+    #[test]
+    fn numbers_add_up() {
+        assert_eq!(Value::new(vec![Value::zero()], vec![Value::integer(1)]), half());
+        assert_eq!(half() + half(), Value::integer(1));
+        assert_eq!(Value::integer(3) - Value::integer(5), Value::integer(-2));
+        assert_eq!(half().number(), Some(Dyadic::new(1, 1)));
+        assert_eq!(half().to_string(), "1/2");
+        assert!(half() < Value::integer(1) && half() > Value::zero());
+    }
+
+    #[test]
+    fn nimbers_add_like_nim_heaps() {
+        assert_eq!(Value::nimber(2) + Value::nimber(3), Value::star());
+        assert_eq!(Value::star() + Value::star(), Value::zero());
+        assert_eq!((Value::star() + Value::nimber(2)).nimber_value(), Some(3));
+        assert_eq!((Value::integer(1) + Value::nimber(2)).to_string(), "1*2");
+        assert_eq!(Value::star().partial_cmp(&Value::zero()), None);
+        assert!(Value::star().left_wins_first() && Value::star().right_wins_first());
+    }
+
+    #[test]
+    fn infinitesimals_and_switches() {
+        assert!(Value::up() > Value::zero());
+        assert_eq!(Value::up() + Value::down(), Value::zero());
+        assert_eq!((Value::up() + Value::star()).to_string(), "↑*");
+        let switch = Value::new(vec![Value::integer(2)], vec![Value::zero()]);
+        assert_eq!(switch.to_string(), "1±1");
+        assert_eq!(switch.partial_cmp(&Value::integer(1)), None);
+        assert!(switch.left_wins_first() && switch.right_wins_first());
+    }
+}
@@ -0,0 +1,214 @@
+use super::Outcome;
+use std::collections::{HashMap, VecDeque};
+use uuid::Uuid;
+
+
+// Single-player puzzles, where the goal is to reach a solved position in as
+// few moves as possible. The heuristic must never overestimate how many
+// moves are left until the puzzle is solved (zero is always fine).
+pub trait Puzzle {
+    fn play(&mut self, mv: Uuid);
+    fn undo(&mut self);
+    fn state(&self) -> i32;
+    fn solved(&self) -> bool;
+    fn heuristic(&self) -> i32;
+    fn possible_moves(&self) -> Vec<Uuid>;
+}
+
+
+// Finds every position reachable from the current one, and fills the table
+// with Outcome::Win(n) for positions whose shortest solution is n moves long,
+// and with Outcome::Loss(0) for positions that cannot be solved at all.
+pub fn solve_puzzle(puzzle: &mut dyn Puzzle, seen: &mut HashMap<i32, Outcome>) -> Outcome {
+    let (graph, goals) = explore(puzzle);
+    let mut parents: HashMap<i32, Vec<i32>> = HashMap::new();
+    for (parent, children) in graph.iter() {
+        for child in children {
+            parents.entry(*child).or_default().push(*parent);
+        }
+    }
+    // Breadth-first search from the solved positions, going backwards
+    let mut remoteness: HashMap<i32, i32> = HashMap::new();
+    let mut queue: VecDeque<i32> = VecDeque::new();
+    for goal in goals {
+        remoteness.insert(goal, 0);
+        queue.push_back(goal);
+    }
+    while let Some(encoded_state) = queue.pop_front() {
+        let rem = remoteness[&encoded_state];
+        for parent in parents.get(&encoded_state).into_iter().flatten() {
+            if !remoteness.contains_key(parent) {
+                remoteness.insert(*parent, rem + 1);
+                queue.push_back(*parent);
+            }
+        }
+    }
+    for encoded_state in graph.keys() {
+        let out = match remoteness.get(encoded_state) {
+            Some(rem) => Outcome::Win(*rem),
+            None => Outcome::Loss(0)
+        };
+        seen.insert(*encoded_state, out);
+    }
+    seen[&puzzle.state()]
+}
+
+
+// Iterative deepening A*. Returns a shortest sequence of moves solving the
+// puzzle from the current position, as long as it is no longer than limit.
+// Uses very little memory, but revisits positions many times.
+pub fn ida_star(puzzle: &mut dyn Puzzle, limit: i32) -> Option<Vec<Uuid>> {
+    let mut bound = puzzle.heuristic();
+    let mut path = Vec::new();
+    let mut trail = vec![puzzle.state()];
+    while bound <= limit {
+        match search(puzzle, 0, bound, &mut path, &mut trail) {
+            Ok(()) => return Some(path),
+            Err(next) if next == i32::MAX => return None,
+            Err(next) => bound = next
+        }
+    }
+    None
+}
+
+
+/* HELPER FUNCTIONS */
+
+// Returns Ok if it solved the puzzle within the bound, or the smallest
+// estimated cost that went over the bound otherwise.
+fn search(
+    puzzle: &mut dyn Puzzle,
+    cost: i32,
+    bound: i32,
+    path: &mut Vec<Uuid>,
+    trail: &mut Vec<i32>
+) -> Result<(), i32> {
+    let estimate = cost + puzzle.heuristic();
+    if estimate > bound {
+        return Err(estimate)
+    }
+    if puzzle.solved() {
+        return Ok(())
+    }
+    let mut next = i32::MAX;
+    for mv in puzzle.possible_moves() {
+        puzzle.play(mv);
+        // Do not walk back into a position on the current path
+        let encoded_state = puzzle.state();
+        if trail.contains(&encoded_state) {
+            puzzle.undo();
+            continue;
+        }
+        path.push(mv);
+        trail.push(encoded_state);
+        let result = search(puzzle, cost + 1, bound, path, trail);
+        trail.pop();
+        puzzle.undo();
+        match result {
+            Ok(()) => return Ok(()),
+            Err(over) => next = next.min(over)
+        }
+        path.pop();
+    }
+    Err(next)
+}
+
+// Maps every reachable position to the positions it leads to, and collects
+// the solved ones. Iterative, as puzzles can be very deep.
+fn explore(puzzle: &mut dyn Puzzle) -> (HashMap<i32, Vec<i32>>, Vec<i32>) {
+    let mut graph: HashMap<i32, Vec<i32>> = HashMap::new();
+    let mut goals = Vec::new();
+    let mut frames: Vec<(i32, Vec<Uuid>)> = Vec::new();
+    frames.push((puzzle.state(), visit(puzzle, &mut graph, &mut goals)));
+    while let Some((parent, pending)) = frames.last_mut() {
+        let parent = *parent;
+        if let Some(mv) = pending.pop() {
+            puzzle.play(mv);
+            let encoded_state = puzzle.state();
+            graph.get_mut(&parent).unwrap().push(encoded_state);
+            if graph.contains_key(&encoded_state) {
+                puzzle.undo();
+            } else {
+                let moves = visit(puzzle, &mut graph, &mut goals);
+                frames.push((encoded_state, moves));
+            }
+        } else {
+            frames.pop();
+            if !frames.is_empty() {
+                puzzle.undo();
+            }
+        }
+    }
+    (graph, goals)
+}
+
+fn visit(puzzle: &mut dyn Puzzle, graph: &mut HashMap<i32, Vec<i32>>, goals: &mut Vec<i32>) -> Vec<Uuid> {
+    graph.insert(puzzle.state(), Vec::new());
+    if puzzle.solved() {
+        goals.push(puzzle.state());
+        return vec![]
+    }
+    puzzle.possible_moves()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{hanoi, sliding_tiles};
+
+    // Plays a sequence of moves and reports whether it solved the puzzle,
+    // putting the puzzle back as it was afterwards.
+    fn solves(puzzle: &mut dyn Puzzle, path: &[Uuid]) -> bool {
+        for mv in path {
+            puzzle.play(*mv);
+        }
+        let solved = puzzle.solved();
+        for _ in path {
+            puzzle.undo();
+        }
+        solved
+    }
+
+    #[test]
+    fn hanoi_takes_one_less_than_a_power_of_two() {
+        for discs in 1..=6 {
+            let mut puzzle = hanoi::Session::new(discs);
+            let out = solve_puzzle(&mut puzzle, &mut HashMap::new());
+            assert!(matches!(out, Outcome::Win(n) if n == (1 << discs) - 1), "{} discs", discs);
+        }
+        for discs in 1..=4 {
+            let mut puzzle = hanoi::Session::new(discs);
+            let path = ida_star(&mut puzzle, 100).unwrap();
+            assert_eq!(path.len(), (1 << discs) - 1, "{} discs", discs);
+            assert!(solves(&mut puzzle, &path));
+        }
+    }
+
+    // Swapping two tiles changes the parity of the arrangement, which no
+    // sequence of slides can undo.
+    #[test]
+    fn odd_sliding_tiles_are_unsolvable() {
+        let mut puzzle = sliding_tiles::Session::with_tiles(2, 3, vec![2, 1, 3, 4, 5, 0]);
+        assert!(matches!(solve_puzzle(&mut puzzle, &mut HashMap::new()), Outcome::Loss(0)));
+    }
+
+    #[test]
+    fn ida_star_finds_shortest_solutions() {
+        let positions = [
+            vec![1, 2, 3, 4, 5, 0],
+            vec![1, 2, 3, 4, 0, 5],
+            vec![0, 1, 2, 4, 5, 3],
+            vec![4, 1, 2, 5, 3, 0],
+            vec![5, 4, 3, 2, 1, 0],
+            vec![0, 5, 4, 3, 2, 1]
+        ];
+        for tiles in positions {
+            let mut puzzle = sliding_tiles::Session::with_tiles(2, 3, tiles.clone());
+            let out = solve_puzzle(&mut puzzle, &mut HashMap::new());
+            let path = ida_star(&mut puzzle, 100).unwrap();
+            assert!(out == Outcome::Win(path.len() as i32), "{:?}", tiles);
+            assert!(solves(&mut puzzle, &path));
+        }
+    }
+}
@@ -0,0 +1,272 @@
+use super::*;
+use std::collections::HashMap;
+
+
+// Parameter values, by parameter name. Every game parameter is an integer.
+pub type Arguments = HashMap<String, i32>;
+
+// Creates a session from complete arguments and a position to start from.
+type Setup = fn(&Arguments, &str) -> Result<Box<dyn Game>, String>;
+
+
+// Describes one parameter a game takes when it is created.
+pub struct Parameter {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub default: i32,
+    pub min: i32,
+    pub max: i32
+}
+
+
+// Everything needed to list a game and to create new sessions of it. The
+// factory gets a value for every parameter, already checked to be in range,
+// and may still reject combinations of values which do not make sense. The
+// setup function does the same, but starts from a position in the game's
+// notation, which must fit the parameters (same board size, no more coins).
+pub struct Entry {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub parameters: Vec<Parameter>,
+    factory: fn(&Arguments) -> Result<Box<dyn Game>, String>,
+    setup: Setup
+}
+
+impl Entry {
+    // Creates a session from the given arguments. Missing parameters take
+    // their default values, and unknown or out of range ones are rejected.
+    pub fn build(&self, arguments: &Arguments) -> Result<Box<dyn Game>, String> {
+        (self.factory)(&self.complete(arguments)?)
+    }
+
+    // Creates a session starting from the given position instead.
+    pub fn setup(&self, arguments: &Arguments, position: &str) -> Result<Box<dyn Game>, String> {
+        (self.setup)(&self.complete(arguments)?, position)
+    }
+
+    // Fills in default values for missing parameters, and checks the rest.
+    pub fn complete(&self, arguments: &Arguments) -> Result<Arguments, String> {
+        for name in arguments.keys() {
+            if !self.parameters.iter().any(|p| p.name == name) {
+                return Err(format!("{} takes no parameter named '{}'.", self.name, name))
+            }
+        }
+        let mut complete = Arguments::new();
+        for parameter in self.parameters.iter() {
+            let value = arguments
+                .get(parameter.name)
+                .copied()
+                .unwrap_or(parameter.default);
+            if value < parameter.min || value > parameter.max {
+                return Err(format!(
+                    "{} must be between {} and {}.",
+                    parameter.name, parameter.min, parameter.max
+                ))
+            }
+            complete.insert(parameter.name.to_string(), value);
+        }
+        Ok(complete)
+    }
+
+    pub fn defaults(&self) -> Arguments {
+        self.parameters
+            .iter()
+            .map(|p| (p.name.to_string(), p.default))
+            .collect()
+    }
+}
+
+
+// All the games that can be created by name, in the order they are listed.
+pub fn games() -> Vec<Entry> {
+    vec![
+        Entry {
+            id: "zero-by-1-2",
+            name: zero_by_1_2::GAME_NAME,
+            description: zero_by_1_2::GAME_DESCRIPTION,
+            parameters: vec![coins()],
+            factory: |args| Ok(Box::new(zero_by_1_2::Session::new(args["coins"]))),
+            setup: |args, position| {
+                let session = zero_by_1_2::Session::from_position(position)?;
+                fits_coins(session.coins_left(), args)?;
+                Ok(Box::new(session))
+            }
+        },
+        Entry {
+            id: "zero-by-1-3-4",
+            name: zero_by_1_3_4::GAME_NAME,
+            description: zero_by_1_3_4::GAME_DESCRIPTION,
+            parameters: vec![coins()],
+            factory: |args| Ok(Box::new(zero_by_1_3_4::Session::new(args["coins"]))),
+            setup: |args, position| {
+                let session = zero_by_1_3_4::Session::from_position(position)?;
+                fits_coins(session.coins_left(), args)?;
+                Ok(Box::new(session))
+            }
+        },
+        Entry {
+            id: "tic-tac-toe",
+            name: tic_tac_toe::GAME_NAME,
+            description: tic_tac_toe::GAME_DESCRIPTION,
+            parameters: vec![
+                height(3, 4),
+                width(3, 4),
+                Parameter {
+                    name: "win",
+                    description: "Marks in a row needed to win",
+                    default: 3,
+                    min: 2,
+                    max: 4
+                }
+            ],
+            factory: |args| {
+                let (height, width, win) = (args["height"], args["width"], args["win"]);
+                if win > height.max(width) {
+                    return Err("The win length cannot be larger than the board.".to_string())
+                }
+                Ok(Box::new(tic_tac_toe::Session::new(height, width, win)))
+            },
+            setup: |args, position| {
+                let session = tic_tac_toe::Session::from_position(position, args["win"])?;
+                fits_board(session.board().height(), session.board().width(), args)?;
+                Ok(Box::new(session))
+            }
+        },
+        Entry {
+            id: "domineering",
+            name: domineering::GAME_NAME,
+            description: domineering::GAME_DESCRIPTION,
+            parameters: vec![height(4, 31), width(4, 31)],
+            factory: |args| {
+                let (height, width) = (args["height"], args["width"]);
+                if height * width > 31 {
+                    return Err("The board can have at most 31 cells.".to_string())
+                }
+                Ok(Box::new(domineering::Session::new(height, width)))
+            },
+            setup: |args, position| {
+                let session = domineering::Session::from_position(position)?;
+                fits_board(session.height(), session.width(), args)?;
+                Ok(Box::new(session))
+            }
+        }
+    ]
+}
+
+
+pub fn find(id: &str) -> Option<Entry> {
+    games().into_iter().find(|entry| entry.id == id)
+}
+
+
+/* HELPER FUNCTIONS */
+
+fn coins() -> Parameter {
+    Parameter {
+        name: "coins",
+        description: "Number of coins in the pool",
+        default: 10,
+        min: 1,
+        max: 1000
+    }
+}
+
+fn height(default: i32, max: i32) -> Parameter {
+    Parameter {
+        name: "height",
+        description: "Number of rows on the board",
+        default,
+        min: 1,
+        max
+    }
+}
+
+fn width(default: i32, max: i32) -> Parameter {
+    Parameter {
+        name: "width",
+        description: "Number of columns on the board",
+        default,
+        min: 1,
+        max
+    }
+}
+
+fn fits_coins(coins: i32, arguments: &Arguments) -> Result<(), String> {
+    if coins > arguments["coins"] {
+        return Err(format!("The position has more than {} coins.", arguments["coins"]))
+    }
+    Ok(())
+}
+
+fn fits_board(height: i32, width: i32, arguments: &Arguments) -> Result<(), String> {
+    if (height, width) != (arguments["height"], arguments["width"]) {
+        return Err(format!(
+            "The position is {}x{}, but the board is {}x{}.",
+            height, width, arguments["height"], arguments["width"]
+        ))
+    }
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Game id, arguments and position to set up.
+    type Case = (&'static str, &'static [(&'static str, i32)], &'static str);
+
+    fn arguments(pairs: &[(&str, i32)]) -> Arguments {
+        pairs.iter().map(|(name, value)| (name.to_string(), *value)).collect()
+    }
+
+    fn setup(id: &str, pairs: &[(&str, i32)], position: &str) -> Result<Box<dyn Game>, String> {
+        find(id).unwrap().setup(&arguments(pairs), position)
+    }
+
+    // Positions written out by a game read back as the same position, both
+    // where the game starts and one move later.
+    #[test]
+    fn positions_round_trip() {
+        let cases: [Case; 6] = [
+            ("zero-by-1-2", &[], "7"),
+            ("zero-by-1-3-4", &[], "0"),
+            ("tic-tac-toe", &[], "O.X/.O./... X"),
+            ("tic-tac-toe", &[("width", 4)], "...O/..X./.... O"),
+            ("domineering", &[], "#.../#.../..../.... R"),
+            ("domineering", &[("height", 2), ("width", 3)], ".../... L")
+        ];
+        for (id, pairs, position) in cases {
+            let mut game = setup(id, pairs, position).unwrap();
+            assert_eq!(game.position(), position);
+            for mv in game.possible_moves() {
+                game.play(mv);
+                let again = setup(id, pairs, &game.position()).unwrap();
+                assert_eq!(again.position(), game.position());
+                assert_eq!(again.state(), game.state());
+                game.undo();
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_positions_are_rejected() {
+        let cases: [Case; 11] = [
+            ("zero-by-1-2", &[], "11"),
+            ("zero-by-1-2", &[], "-1"),
+            ("zero-by-1-3-4", &[("coins", 3)], "4"),
+            ("zero-by-1-3-4", &[], "seven"),
+            ("tic-tac-toe", &[], ".../..."),
+            ("tic-tac-toe", &[], "..../..../...."),
+            ("tic-tac-toe", &[], "O../.../... O"),
+            ("tic-tac-toe", &[], "OO./.../... X"),
+            ("tic-tac-toe", &[], "O../..../..."),
+            ("domineering", &[], "#.../..../..../...."),
+            ("domineering", &[], "#.../#.../..../.... L")
+        ];
+        for (id, pairs, position) in cases {
+            assert!(setup(id, pairs, position).is_err(), "{} {}", id, position);
+        }
+    }
+}
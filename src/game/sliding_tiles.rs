@@ -0,0 +1,155 @@
+use super::puzzle::Puzzle;
+use bimap::BiMap;
+use uuid::Uuid;
+
+
+pub const GAME_NAME: &str = "Sliding tiles";
+pub const GAME_DESCRIPTION: &str =
+"Sliding tiles is played on an NxM frame holding numbered tiles and a single
+empty slot. A move slides a tile next to the empty slot into it. The puzzle
+is solved once the tiles are in increasing order, reading left to right and
+top to bottom, with the empty slot in the bottom right corner.";
+
+
+// Direction in which the empty slot moves.
+#[derive(Eq, PartialEq, Hash, Clone, Copy)]
+pub enum Move {
+    Up,
+    Down,
+    Left,
+    Right
+}
+
+
+pub struct Session {
+    tiles: Vec<i32>,
+    height: i32,
+    width: i32,
+    moves: BiMap<Uuid, Move>,
+    stack: Vec<Move>
+}
+
+impl Session {
+    pub fn new(height: i32, width: i32) -> Self {
+        let cells = height * width;
+        let mut tiles: Vec<i32> = (1..cells).collect();
+        tiles.push(0);
+        Self::with_tiles(height, width, tiles)
+    }
+
+    // Tiles are listed row by row, with zero standing for the empty slot.
+    pub fn with_tiles(height: i32, width: i32, tiles: Vec<i32>) -> Self {
+        if height < 1 || width < 1 || height * width > 12 {
+            panic!("Frame must have between 1 and 12 cells.");
+        }
+        let mut sorted = tiles.clone();
+        sorted.sort();
+        if sorted != (0..height * width).collect::<Vec<i32>>() {
+            panic!("Tiles must be a permutation of 0 to N * M - 1.");
+        }
+        let mut moves: BiMap<Uuid, Move> = BiMap::new();
+        for mv in [Move::Up, Move::Down, Move::Left, Move::Right] {
+            moves.insert(Uuid::new_v4(), mv);
+        }
+        Session {
+            tiles,
+            height,
+            width,
+            moves,
+            stack: Vec::new()
+        }
+    }
+
+    pub fn print(&self) {
+        for row in self.tiles.chunks(self.width as usize) {
+            for tile in row {
+                if *tile == 0 {
+                    print!("   ");
+                } else {
+                    print!("{:>2} ", tile);
+                }
+            }
+            println!();
+        }
+    }
+
+    fn blank(&self) -> (i32, i32) {
+        let index = self.tiles.iter().position(|t| *t == 0).unwrap() as i32;
+        (index / self.width, index % self.width)
+    }
+
+    fn target(&self, mv: Move) -> Option<(i32, i32)> {
+        let (i, j) = self.blank();
+        let (i, j) = match mv {
+            Move::Up => (i - 1, j),
+            Move::Down => (i + 1, j),
+            Move::Left => (i, j - 1),
+            Move::Right => (i, j + 1)
+        };
+        if i < 0 || i >= self.height || j < 0 || j >= self.width {
+            None
+        } else {
+            Some((i, j))
+        }
+    }
+
+    fn slide(&mut self, mv: Move) {
+        let (i, j) = self.blank();
+        let (ti, tj) = self.target(mv).expect("Illegal move!");
+        self.tiles.swap((i * self.width + j) as usize, (ti * self.width + tj) as usize);
+    }
+}
+
+impl Puzzle for Session {
+    fn play(&mut self, mv: Uuid) {
+        let mv = *self.moves.get_by_left(&mv).expect("Error finding move.");
+        self.slide(mv);
+        self.stack.push(mv);
+    }
+
+    fn undo(&mut self) {
+        let mv = self.stack.pop().expect("Expected move, found nothing.");
+        self.slide(match mv {
+            Move::Up => Move::Down,
+            Move::Down => Move::Up,
+            Move::Left => Move::Right,
+            Move::Right => Move::Left
+        });
+    }
+
+    // Rank of the arrangement among all permutations of the tiles.
+    fn state(&self) -> i32 {
+        let mut hash = 0;
+        for (i, tile) in self.tiles.iter().enumerate() {
+            let smaller = self.tiles[i + 1..].iter().filter(|t| *t < tile).count();
+            hash = hash * (self.tiles.len() - i) as i32 + smaller as i32;
+        }
+        hash
+    }
+
+    fn solved(&self) -> bool {
+        self.heuristic() == 0
+    }
+
+    // Sum over all tiles of their distance to where they belong.
+    fn heuristic(&self) -> i32 {
+        let mut total = 0;
+        for (index, tile) in self.tiles.iter().enumerate() {
+            if *tile == 0 {
+                continue;
+            }
+            let (i, j) = (index as i32 / self.width, index as i32 % self.width);
+            let (gi, gj) = ((tile - 1) / self.width, (tile - 1) % self.width);
+            total += (i - gi).abs() + (j - gj).abs();
+        }
+        total
+    }
+
+    fn possible_moves(&self) -> Vec<Uuid> {
+        self.moves
+            .iter()
+            .filter(|(_, mv)| self.target(**mv).is_some())
+            .map(|(id, _)| *id)
+            .collect()
+    }
+}
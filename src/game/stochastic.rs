@@ -0,0 +1,228 @@
+use std::collections::HashMap;
+use uuid::Uuid;
+
+
+#[derive(Eq, Hash, PartialEq, Clone, Copy, Debug)]
+pub enum Player {
+    First,
+    Second
+}
+
+
+// Moves out of a position are either chosen by the player whose turn it is,
+// or drawn at random with the attached probabilities (which add up to one).
+pub enum Moves {
+    Choice(Vec<Uuid>),
+    Chance(Vec<(Uuid, f64)>)
+}
+
+
+// Stochastic games are two-player zero-sum games with chance positions. All
+// values are from the perspective of the first player, who tries to make
+// them as large as possible, while the second player tries to minimize them.
+// A value can be a win probability or any other expected score.
+pub trait Stochastic {
+    fn play(&mut self, mv: Uuid);
+    fn undo(&mut self);
+    fn state(&self) -> i32;
+    fn turn(&self) -> Player;
+    fn payoff(&self) -> Option<f64>;
+    fn possible_moves(&self) -> Moves;
+}
+
+
+// Computes the value of the current position assuming both players play to
+// optimize their expected values. Only works for acyclic games.
+pub fn expectimax(game: &mut dyn Stochastic, seen: &mut HashMap<i32, f64>) -> f64 {
+    if let Some(val) = game.payoff() {
+        return val
+    }
+    match game.possible_moves() {
+        Moves::Choice(moves) => {
+            let mut best: Option<f64> = None;
+            for mv in moves {
+                let val = child_value(game, mv, seen);
+                best = match (best, game.turn()) {
+                    (None, _) => Some(val),
+                    (Some(b), Player::First) => Some(b.max(val)),
+                    (Some(b), Player::Second) => Some(b.min(val))
+                };
+            }
+            best.expect("Non-terminal position with no moves.")
+        },
+        Moves::Chance(moves) => {
+            let mut expected = 0.0;
+            for (mv, prob) in moves {
+                expected += prob * child_value(game, mv, seen);
+            }
+            expected
+        }
+    }
+}
+
+
+// Returns the move the player to move should choose according to the values
+// in a table filled by the expectimax solver, if the position is a choice.
+pub fn best_move(game: &mut dyn Stochastic, seen: &mut HashMap<i32, f64>) -> Option<Uuid> {
+    let moves = match game.possible_moves() {
+        Moves::Choice(moves) => moves,
+        Moves::Chance(_) => return None
+    };
+    let mut best: Option<(Uuid, f64)> = None;
+    for mv in moves {
+        let val = child_value(game, mv, seen);
+        best = match (best, game.turn()) {
+            (Some((_, b)), Player::First) if b >= val => best,
+            (Some((_, b)), Player::Second) if b <= val => best,
+            _ => Some((mv, val))
+        };
+    }
+    best.map(|(mv, _)| mv)
+}
+
+
+fn child_value(game: &mut dyn Stochastic, mv: Uuid, seen: &mut HashMap<i32, f64>) -> f64 {
+    game.play(mv);
+    let encoded_state = game.state();
+    let val = if let Some(val) = seen.get(&encoded_state).copied() {
+        val
+    } else {
+        let val = expectimax(game, seen);
+        seen.insert(encoded_state, val);
+        val
+    };
+    game.undo();
+    val
+}
+
+
+// Computes the values of every position reachable from the current one by
+// repeatedly sweeping over them until no value changes by more than the
+// tolerance. Unlike expectimax, this also works for games with cycles.
+pub fn value_iteration(
+    game: &mut dyn Stochastic,
+    values: &mut HashMap<i32, f64>,
+    tolerance: f64
+) -> f64 {
+    let graph = explore(game);
+    // Sweeping over plain vectors is much faster than hashing every state
+    let mut states: Vec<i32> = graph.keys().copied().collect();
+    states.sort();
+    let index: HashMap<i32, usize> = states
+        .iter()
+        .enumerate()
+        .map(|(i, s)| (*s, i))
+        .collect();
+    let mut current: Vec<f64> = states
+        .iter()
+        .map(|s| match &graph[s] {
+            Node::Terminal(val) => *val,
+            _ => values.get(s).copied().unwrap_or(0.0)
+        })
+        .collect();
+    let nodes: Vec<_> = states
+        .iter()
+        .map(|s| match &graph[s] {
+            Node::Terminal(_) => (None, vec![]),
+            Node::Choice(player, children) => (
+                Some(*player),
+                children.iter().map(|c| (index[c], 1.0)).collect()
+            ),
+            Node::Chance(children) => (
+                None,
+                children.iter().map(|(c, prob)| (index[c], *prob)).collect()
+            )
+        })
+        .collect();
+    loop {
+        let mut delta: f64 = 0.0;
+        for (i, (player, children)) in nodes.iter().enumerate() {
+            if children.is_empty() {
+                continue;
+            }
+            let val = match player {
+                Some(Player::First) => children
+                    .iter()
+                    .map(|(c, _)| current[*c])
+                    .fold(f64::NEG_INFINITY, f64::max),
+                Some(Player::Second) => children
+                    .iter()
+                    .map(|(c, _)| current[*c])
+                    .fold(f64::INFINITY, f64::min),
+                None => children
+                    .iter()
+                    .map(|(c, prob)| prob * current[*c])
+                    .sum()
+            };
+            delta = delta.max((current[i] - val).abs());
+            current[i] = val;
+        }
+        if delta < tolerance {
+            break;
+        }
+    }
+    for (encoded_state, val) in states.into_iter().zip(current) {
+        values.insert(encoded_state, val);
+    }
+    values[&game.state()]
+}
+
+
+/* HELPER FUNCTIONS */
+
+enum Node {
+    Terminal(f64),
+    Choice(Player, Vec<i32>),
+    Chance(Vec<(i32, f64)>)
+}
+
+// Finds every position reachable from the current one, along with the
+// positions each of them leads to. Iterative, as games might be cyclic
+// and their positions can be very far away from each other.
+fn explore(game: &mut dyn Stochastic) -> HashMap<i32, Node> {
+    let mut graph: HashMap<i32, Node> = HashMap::new();
+    let mut frames: Vec<(i32, Vec<(Uuid, f64)>)> = Vec::new();
+    let root = game.state();
+    frames.push((root, visit(game, &mut graph)));
+    while let Some((parent, pending)) = frames.last_mut() {
+        let parent = *parent;
+        if let Some((mv, prob)) = pending.pop() {
+            game.play(mv);
+            let encoded_state = game.state();
+            match graph.get_mut(&parent) {
+                Some(Node::Choice(_, children)) => children.push(encoded_state),
+                Some(Node::Chance(children)) => children.push((encoded_state, prob)),
+                _ => panic!("Moves out of a terminal position.")
+            }
+            if graph.contains_key(&encoded_state) {
+                game.undo();
+            } else {
+                let moves = visit(game, &mut graph);
+                frames.push((encoded_state, moves));
+            }
+        } else {
+            frames.pop();
+            if !frames.is_empty() {
+                game.undo();
+            }
+        }
+    }
+    graph
+}
+
+fn visit(game: &mut dyn Stochastic, graph: &mut HashMap<i32, Node>) -> Vec<(Uuid, f64)> {
+    if let Some(val) = game.payoff() {
+        graph.insert(game.state(), Node::Terminal(val));
+        return vec![]
+    }
+    match game.possible_moves() {
+        Moves::Choice(moves) => {
+            graph.insert(game.state(), Node::Choice(game.turn(), vec![]));
+            moves.into_iter().map(|mv| (mv, 1.0)).collect()
+        },
+        Moves::Chance(moves) => {
+            graph.insert(game.state(), Node::Chance(vec![]));
+            moves
+        }
+    }
+}
@@ -5,7 +5,8 @@
 pub mod board;
 
 
-use super::{Game, Outcome};
+use super::{Game, Outcome, encode_coordinate, decode_coordinate, column_header};
+use super::{split_position, join_position};
 use board::Board;
 use bimap::BiMap;
 use uuid::Uuid;
@@ -32,7 +33,9 @@ pub struct Place {
 pub struct Session {
     board: Board,
     moves: BiMap<Uuid, Move>,
-    stack: Vec<Move>
+    stack: Vec<Move>,
+    // Marks already on the board when the session was set up
+    offset: usize
 }
 
 impl Session {
@@ -40,10 +43,55 @@ impl Session {
         Session {
             board: Board::new(height, width, win),
             moves: Self::map_possible_moves(height, width),
-            stack: Vec::new()
+            stack: Vec::new(),
+            offset: 0
         }
     }
 
+    // Sets up a position written as in position(), like "O.X/.O./... X",
+    // with one row per slash from top to bottom and "." for empty squares.
+    // O always moves first, so the side to move (which can be left out)
+    // must agree with the number of marks of each kind.
+    pub fn from_position(text: &str, win: i32) -> Result<Self, String> {
+        let (rows, side) = split_position(text)?;
+        let (height, width) = (rows.len() as i32, rows[0].len() as i32);
+        if win < 1 || win > height.max(width) {
+            return Err("The win length does not fit on the board.".to_string())
+        }
+        let mut session = Session::new(height, width, win);
+        let (mut crosses, mut circles) = (0, 0);
+        for (i, row) in rows.iter().enumerate() {
+            for (j, cell) in row.iter().enumerate() {
+                let symbol = match cell.to_ascii_uppercase() {
+                    'X' => Some(true),
+                    'O' => Some(false),
+                    '.' => None,
+                    other => return Err(format!("Unknown square '{}' in the position.", other))
+                };
+                match symbol {
+                    Some(true) => crosses += 1,
+                    Some(false) => circles += 1,
+                    None => ()
+                }
+                session.board.place(symbol, i as i32, j as i32);
+            }
+        }
+        let to_move = match circles - crosses {
+            0 => "O",
+            1 => "X",
+            _ => return Err("O moves first, so it must have as many marks as X or one more.".to_string())
+        };
+        if side.is_some_and(|s| !s.eq_ignore_ascii_case(to_move)) {
+            return Err(format!("It is {}'s turn in this position.", to_move))
+        }
+        session.offset = (crosses + circles) as usize;
+        Ok(session)
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
     fn map_possible_moves(height: i32, width: i32) -> BiMap<Uuid, Move> {
         let mut moves: BiMap<Uuid, Move> = BiMap::new();
         for i in 0..height {
@@ -58,12 +106,20 @@ impl Session {
         moves
     }
 
+    fn side_to_move(&self) -> &'static str {
+        if (self.offset + self.stack.len()).is_multiple_of(2) {
+            "O"
+        } else {
+            "X"
+        }
+    }
+
     fn move_from_uuid(&self, id: Uuid) -> Move {
         *self.moves.get_by_left(&id).unwrap()
     }
 
     fn retain_move_candidates(&self, v: &mut Vec<Uuid>) {
-        if self.stack.len() % 2 == 0 {
+        if (self.offset + self.stack.len()).is_multiple_of(2) {
             // O's turn, eliminate Xs from v
             v.retain(|&o| match self.move_from_uuid(o) {
                 Move::O(_) => true,
@@ -84,14 +140,14 @@ impl Game for Session {
         let mv = self.moves.get_by_left(&mv).expect("Could not find move.");
         match mv {
             Move::O(place) => {
-                if let None = self.board.symbol_at(place.i, place.j) {
+                if self.board.symbol_at(place.i, place.j).is_none() {
                     self.board.place(Some(false), place.i, place.j);
                 } else {
                     panic!("Attempted illegal move.");
                 }
             },
             Move::X(place) => {
-                if let None = self.board.symbol_at(place.i, place.j) {
+                if self.board.symbol_at(place.i, place.j).is_none() {
                     self.board.place(Some(true), place.i, place.j);
                 } else {
                     panic!("Attempted illegal move.");
@@ -110,17 +166,21 @@ impl Game for Session {
         self.board.place(None, place.i, place.j);
     }
 
+    // Nobody moves once the game is over, even if there are empty squares.
     fn possible_moves(&self) -> Vec<Uuid> {
         let mut result: Vec<Uuid> = Vec::new();
+        if self.board.outcome().is_some() {
+            return result
+        }
         for (id, mv) in self.moves.iter() {
             match mv {
                 Move::O(place) => {
-                    if let None = self.board.symbol_at(place.i, place.j) {
+                    if self.board.symbol_at(place.i, place.j).is_none() {
                         result.push(*id);
                     }
                 },
                 Move::X(place) => {
-                    if let None = self.board.symbol_at(place.i, place.j) {
+                    if self.board.symbol_at(place.i, place.j).is_none() {
                         result.push(*id);
                     }
                 }
@@ -130,6 +190,13 @@ impl Game for Session {
         result
     }
 
+    fn history(&self) -> Vec<Uuid> {
+        self.stack
+            .iter()
+            .map(|mv| *self.moves.get_by_right(mv).expect("Could not find move."))
+            .collect()
+    }
+
     fn state(&self) -> i32 {
         self.board.hash()
     }
@@ -137,4 +204,64 @@ impl Game for Session {
     fn outcome(&self) -> Option<Outcome> {
         self.board.outcome()
     }
-}
\ No newline at end of file
+
+    fn encode_move(&self, mv: Uuid) -> String {
+        match self.move_from_uuid(mv) {
+            Move::X(place) | Move::O(place) => encode_coordinate(place.i, place.j)
+        }
+    }
+
+    // Moves are written as the coordinate of the square to mark.
+    fn decode_move(&self, text: &str) -> Option<Uuid> {
+        let (i, j) = decode_coordinate(text)?;
+        self.possible_moves()
+            .into_iter()
+            .find(|id| match self.move_from_uuid(*id) {
+                Move::X(place) | Move::O(place) => place.i == i && place.j == j
+            })
+    }
+
+    fn render(&self) -> String {
+        let (height, width) = (self.board.height(), self.board.width());
+        let mut lines = vec![column_header(width)];
+        for i in 0..height {
+            let mut line = format!("{:>2} ", i + 1);
+            for j in 0..width {
+                line.push_str(match self.board.symbol_at(i, j) {
+                    Some(true) => " X",
+                    Some(false) => " O",
+                    None => " ."
+                });
+            }
+            lines.push(line);
+        }
+        lines.join("\n")
+    }
+
+    fn position(&self) -> String {
+        join_position(rows(&self.board), self.side_to_move())
+    }
+
+    // Rotations and reflections of the board leave the game the same.
+    fn canonical_position(&self) -> String {
+        join_position(rows(&self.board.canonical()), self.side_to_move())
+    }
+}
+
+
+/* HELPER FUNCTIONS */
+
+// Rows of a board as written in positions.
+fn rows(board: &Board) -> Vec<String> {
+    (0..board.height())
+        .map(|i| {
+            (0..board.width())
+                .map(|j| match board.symbol_at(i, j) {
+                    Some(true) => 'X',
+                    Some(false) => 'O',
+                    None => '.'
+                })
+                .collect()
+        })
+        .collect()
+}
@@ -29,6 +29,14 @@ impl Board {
         }
     }
 
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
     pub fn symbol_at(&self, i: i32, j: i32) -> Option<bool> {
         if i >= self.height || i < 0 || j >= self.width || j < 0 {
             self.print();
@@ -59,7 +67,7 @@ impl Board {
         let mut moves_made = 0;
         for i in self.contents.iter() {
             for j in i {
-                if let Some(_) = j {
+                if j.is_some() {
                     moves_made += 1;
                 }
             }
@@ -149,7 +157,7 @@ impl Board {
                     print!("  ");
                 }
             }
-            print!("\n");
+            println!();
         }
     }
 
@@ -182,9 +190,7 @@ impl Board {
                     = self.symbol_at(i, j);
             }
         }
-        let temp = self.width;
-        self.width = self.height;
-        self.height = temp;
+        std::mem::swap(&mut self.width, &mut self.height);
         self.contents = new_contents;
     }
 
@@ -199,7 +205,7 @@ impl Board {
             count = 1;
             for i in 1..self.height {
                 curr = self.symbol_at(i, j);
-                if curr == kind && curr != None {
+                if curr == kind && curr.is_some() {
                     count += 1;
                 } else {
                     count = 1;
@@ -228,7 +234,7 @@ impl Board {
             count = 1;
             for j in 1..self.width {
                 curr = self.symbol_at(i, j);
-                if curr == kind && curr != None {
+                if curr == kind && curr.is_some() {
                     count += 1;
                 } else {
                     count = 1;
@@ -268,7 +274,7 @@ impl Board {
     fn check_diag_win_from(&self, i: i32, j: i32) -> bool {
         if let Some(first)= self.symbol_at(i, j) {
             for k in 1..self.win {
-                if let None = self.symbol_at(i+k, j+k) {
+                if self.symbol_at(i+k, j+k).is_none() {
                     return false;
                 }
                 if let Some(kind) = self.symbol_at(i+k, j+k) {
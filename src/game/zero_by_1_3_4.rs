@@ -49,6 +49,14 @@ impl Session {
         }
     }
 
+    // Positions are written as the number of coins left, as in "7".
+    pub fn from_position(text: &str) -> Result<Self, String> {
+        match text.trim().parse() {
+            Ok(coins) if coins >= 0 => Ok(Session::new(coins)),
+            _ => Err(format!("Expected a number of coins, got '{}'.", text.trim()))
+        }
+    }
+
     pub fn coins_left(&self) -> i32 {
         self.coins
     }
@@ -80,7 +88,7 @@ impl Game for Session {
                 }
             }
         }
-        self.stack.push(mv.clone());
+        self.stack.push(mv);
     }
 
     fn undo(&mut self) {
@@ -107,6 +115,10 @@ impl Game for Session {
         }
     }
 
+    fn history(&self) -> Vec<Uuid> {
+        self.stack.iter().map(|mv| self.move_uuid(*mv)).collect()
+    }
+
     fn state(&self) -> i32 {
         self.coins
     }
@@ -118,4 +130,37 @@ impl Game for Session {
             None
         }
     }
-}
\ No newline at end of file
+
+    fn encode_move(&self, mv: Uuid) -> String {
+        match *self.moves.get_by_left(&mv).expect("Error finding move.") {
+            Move::One => "1".to_string(),
+            Move::Three => "3".to_string(),
+            Move::Four => "4".to_string()
+        }
+    }
+
+    // Moves are written as the amount of coins to take.
+    fn decode_move(&self, text: &str) -> Option<Uuid> {
+        let mv = match text.trim() {
+            "1" => Move::One,
+            "3" => Move::Three,
+            "4" => Move::Four,
+            _ => return None
+        };
+        let id = self.move_uuid(mv);
+        if self.possible_moves().contains(&id) {
+            Some(id)
+        } else {
+            None
+        }
+    }
+
+    fn render(&self) -> String {
+        let coins = vec!["o"; self.coins as usize].join(" ");
+        format!("Coins left: {}\n{}", self.coins, coins)
+    }
+
+    fn position(&self) -> String {
+        self.coins.to_string()
+    }
+}
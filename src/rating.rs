@@ -0,0 +1,277 @@
+use std::collections::HashMap;
+use std::f64::consts::{LN_10, PI};
+use std::fs;
+use crate::tournament::Results;
+
+
+// Everyone starts here, with the largest possible uncertainty
+pub const INITIAL_RATING: f64 = 1500.0;
+pub const INITIAL_DEVIATION: f64 = 350.0;
+
+// How much an Elo rating can move in a single tournament (see update)
+const ELO_K: f64 = 32.0;
+
+// How much uncertainty grows before each tournament an agent plays in, so
+// that old ratings can still move. Agents which sit a tournament out keep
+// their ratings as they were.
+const GLICKO_C: f64 = 30.0;
+
+const Q: f64 = LN_10 / 400.0;
+
+
+// An agent's Elo rating, along with its Glicko rating and rating deviation
+// (roughly, the standard deviation of how strong the agent really is).
+#[derive(Clone, Copy)]
+pub struct Rating {
+    pub elo: f64,
+    pub glicko: f64,
+    pub deviation: f64,
+    pub games: u32
+}
+
+impl Default for Rating {
+    fn default() -> Self {
+        Rating {
+            elo: INITIAL_RATING,
+            glicko: INITIAL_RATING,
+            deviation: INITIAL_DEVIATION,
+            games: 0
+        }
+    }
+}
+
+
+// Ratings by agent name, for the one game and set of parameters they were
+// earned on (such as "tic-tac-toe(height=3,width=3,win=3)"). Agents are only
+// comparable when rated on the same game, so ratings for different games
+// are kept apart.
+#[derive(Default)]
+pub struct Ratings {
+    game: Option<String>,
+    ratings: HashMap<String, Rating>
+}
+
+impl Ratings {
+    pub fn new() -> Self {
+        Ratings { game: None, ratings: HashMap::new() }
+    }
+
+    // Ties ratings which are not tied to a game yet to the given one, and
+    // refuses to go on with ratings from a different game.
+    pub fn set_game(&mut self, game: &str) -> Result<(), String> {
+        match self.game.as_deref() {
+            Some(other) if other != game => Err(format!(
+                "The ratings are for {}, not for {}.", other, game
+            )),
+            _ => {
+                self.game = Some(game.to_string());
+                Ok(())
+            }
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Rating {
+        self.ratings.get(name).copied().unwrap_or_default()
+    }
+
+    // Treats a whole tournament as a single rating period, so the order in
+    // which its games were played does not matter. Every agent is rated
+    // against the others' ratings from before the tournament.
+    //
+    // Glicko is the usual one-period update. Elo, on the other hand, is not
+    // the usual game-by-game update, which tournament results cannot replay
+    // as they only keep counts. Instead, it scores the tournament like a
+    // single game, whose result is the agent's average score and whose
+    // expected result is the average of what it was expected to score in
+    // each game. So an agent gains or loses at most ELO_K points per
+    // tournament, however many games it played. This keeps one lopsided
+    // tournament from sending ratings off by thousands of points, while
+    // ratings still drift apart over many tournaments.
+    pub fn update(&mut self, results: &Results) {
+        let before: Vec<Rating> = results.names.iter().map(|n| self.get(n)).collect();
+        let mut after = Vec::new();
+        for (i, name) in results.names.iter().enumerate() {
+            let mut rating = before[i];
+            let mut elo_change = 0.0;
+            let mut variance_inverse = 0.0;
+            let mut glicko_sum = 0.0;
+            let mut games = 0;
+            // Uncertainty grows between rating periods
+            let deviation = (rating.deviation.powi(2) + GLICKO_C.powi(2))
+                .sqrt()
+                .min(INITIAL_DEVIATION);
+            for (j, tally) in results.results[i].iter().enumerate() {
+                if i == j || tally.games() == 0 {
+                    continue
+                }
+                let opponent = before[j];
+                let n = tally.games() as f64;
+                let points = tally.wins as f64 + 0.5 * tally.ties as f64;
+                elo_change += ELO_K * (points - n * elo_expected(rating.elo, opponent.elo));
+                let g = glicko_g(opponent.deviation);
+                let expected = glicko_expected(rating.glicko, opponent.glicko, opponent.deviation);
+                variance_inverse += n * Q * Q * g * g * expected * (1.0 - expected);
+                glicko_sum += g * (points - n * expected);
+                games += tally.games();
+            }
+            if games == 0 {
+                continue
+            }
+            let precision = 1.0 / deviation.powi(2) + variance_inverse;
+            rating.elo += elo_change / games as f64;
+            rating.glicko += Q / precision * glicko_sum;
+            rating.deviation = (1.0 / precision).sqrt();
+            rating.games += games;
+            after.push((name.clone(), rating));
+        }
+        self.ratings.extend(after);
+    }
+
+    // Agents sorted from the highest Glicko rating down.
+    pub fn standings(&self) -> Vec<(String, Rating)> {
+        let mut standings: Vec<(String, Rating)> = self.ratings
+            .iter()
+            .map(|(name, rating)| (name.clone(), *rating))
+            .collect();
+        standings.sort_by(|a, b| b.1.glicko.total_cmp(&a.1.glicko));
+        standings
+    }
+
+    pub fn print(&self) {
+        if let Some(game) = self.game.as_deref() {
+            println!("Ratings for {}\n", game);
+        }
+        println!("{:<16}{:>8}{:>9}{:>7}{:>8}", "Agent", "Elo", "Glicko", "RD", "Games");
+        println!("{}", "-".repeat(48));
+        for (name, rating) in self.standings() {
+            println!(
+                "{:<16}{:>8.0}{:>9.0}{:>7.0}{:>8}",
+                name, rating.elo, rating.glicko, rating.deviation, rating.games
+            );
+        }
+    }
+
+    // A first line naming the game, and then one agent per line, with its
+    // ratings separated by tabs. A missing file is the same as an empty one,
+    // so that the first run can create it.
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Ratings::new()),
+            Err(e) => return Err(format!("Could not read {}: {}", path, e))
+        };
+        let mut lines = text.lines().filter(|l| !l.is_empty());
+        let game = match lines.next() {
+            Some(line) => match line.strip_prefix("game\t") {
+                Some(game) => Some(game.to_string()),
+                None => return Err(format!("{} does not say which game its ratings are for.", path))
+            },
+            None => None
+        };
+        let mut ratings = HashMap::new();
+        for line in lines {
+            let invalid = || format!("Invalid line in {}: '{}'", path, line);
+            let parts: Vec<&str> = line.split('\t').collect();
+            if parts.len() != 5 {
+                return Err(invalid())
+            }
+            let number = |i: usize| parts[i].parse::<f64>().map_err(|_| invalid());
+            let rating = Rating {
+                elo: number(1)?,
+                glicko: number(2)?,
+                deviation: number(3)?,
+                games: parts[4].parse().map_err(|_| invalid())?
+            };
+            ratings.insert(parts[0].to_string(), rating);
+        }
+        Ok(Ratings { game, ratings })
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let game = self.game.as_deref().ok_or("Ratings must be for a game to be saved.")?;
+        let mut lines = vec![format!("game\t{}", game)];
+        lines.extend(
+            self.standings()
+                .into_iter()
+                .map(|(name, r)| format!("{}\t{}\t{}\t{}\t{}", name, r.elo, r.glicko, r.deviation, r.games))
+        );
+        lines.push(String::new());
+        fs::write(path, lines.join("\n"))
+            .map_err(|e| format!("Could not write {}: {}", path, e))
+    }
+}
+
+
+/* HELPER FUNCTIONS */
+
+fn elo_expected(rating: f64, opponent: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf((opponent - rating) / 400.0))
+}
+
+// Discounts games against opponents whose strength is uncertain
+fn glicko_g(deviation: f64) -> f64 {
+    1.0 / (1.0 + 3.0 * Q * Q * deviation * deviation / (PI * PI)).sqrt()
+}
+
+fn glicko_expected(rating: f64, opponent: f64, deviation: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-glicko_g(deviation) * (rating - opponent) / 400.0))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tournament::Tally;
+
+    // A tournament between two agents where the first won every game.
+    fn sweep(names: [&str; 2], games: u32) -> Results {
+        let won = Tally { wins: games, losses: 0, ties: 0 };
+        let lost = Tally { wins: 0, losses: games, ties: 0 };
+        Results {
+            names: names.iter().map(|n| n.to_string()).collect(),
+            results: vec![vec![Tally::default(), won], vec![lost, Tally::default()]]
+        }
+    }
+
+    #[test]
+    fn elo_moves_at_most_k_per_tournament() {
+        let mut ratings = Ratings::new();
+        ratings.update(&sweep(["Perfect", "Random"], 100));
+        assert!((ratings.get("Perfect").elo - (INITIAL_RATING + ELO_K / 2.0)).abs() < 1e-9);
+        assert!((ratings.get("Random").elo - (INITIAL_RATING - ELO_K / 2.0)).abs() < 1e-9);
+        for _ in 0..100 {
+            ratings.update(&sweep(["Perfect", "Random"], 100));
+        }
+        let gap = ratings.get("Perfect").elo - ratings.get("Random").elo;
+        assert!(gap > 400.0 && gap < 101.0 * ELO_K);
+    }
+
+    #[test]
+    fn agents_not_in_a_tournament_keep_their_ratings() {
+        let mut ratings = Ratings::new();
+        ratings.update(&sweep(["Perfect", "Random"], 10));
+        let before = ratings.get("Random");
+        ratings.update(&sweep(["Perfect", "Random #2"], 10));
+        let after = ratings.get("Random");
+        assert_eq!((before.elo, before.glicko, before.deviation), (after.elo, after.glicko, after.deviation));
+        assert_eq!(ratings.get("Random #2").games, 10);
+    }
+
+    #[test]
+    fn ratings_stay_with_their_game() {
+        let path = std::env::temp_dir().join(format!("game_tree_ratings_{}.tsv", std::process::id()));
+        let path = path.to_str().unwrap();
+        let mut ratings = Ratings::load(path).unwrap();
+        ratings.set_game("zero-by-1-2(coins=10)").unwrap();
+        ratings.update(&sweep(["Perfect", "Random"], 10));
+        ratings.save(path).unwrap();
+
+        let mut loaded = Ratings::load(path).unwrap();
+        std::fs::remove_file(path).ok();
+        assert_eq!(loaded.get("Perfect").elo, ratings.get("Perfect").elo);
+        assert_eq!(loaded.get("Random").games, 10);
+        assert!(loaded.set_game("zero-by-1-2(coins=11)").is_err());
+        assert!(loaded.set_game("zero-by-1-2(coins=10)").is_ok());
+        assert!(Ratings::new().save(path).is_err());
+    }
+}
@@ -0,0 +1,291 @@
+use std::fmt;
+use std::fs;
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::game::{Game, Outcome};
+use crate::game::registry::{self, Arguments};
+
+
+// A recorded match in a PGN-like text format. Header tags come first, one
+// per line, followed by the moves in the game's own notation:
+//
+//     [Game "tic-tac-toe"]
+//     [Parameters "height=3 width=3 win=3"]
+//     [First "Human"]
+//     [Second "Perfect"]
+//     [Date "2023.04.04"]
+//     [Result "1/2-1/2"]
+//
+//     1. b2 a1 2. c3 c1 3. b1 b3 4. a3 c2 5. a2 1/2-1/2
+//
+// Results are given from the first player's point of view: "1-0" when the
+// first player won, "0-1" when they lost, "1/2-1/2" for a tie, and "*" when
+// the match was not over. Matches which did not start from the usual first
+// position have a Position tag with the starting position in the game's
+// notation, and the first player is whoever was to move in it.
+pub struct Record {
+    tags: Vec<(String, String)>,
+    pub moves: Vec<String>
+}
+
+impl Record {
+    pub fn new(game_id: &str, arguments: &Arguments) -> Self {
+        let mut record = Record { tags: Vec::new(), moves: Vec::new() };
+        record.set_tag("Game", game_id);
+        record.set_tag("Parameters", &format_arguments(arguments));
+        record.set_tag("First", "?");
+        record.set_tag("Second", "?");
+        record.set_tag("Date", &today());
+        record.set_tag("Result", "*");
+        record
+    }
+
+    // Records the moves played so far in a session, along with the result
+    // if it is over. Leaves the session as it found it.
+    pub fn from_session(game_id: &str, arguments: &Arguments, game: &mut dyn Game) -> Self {
+        let mut record = Record::new(game_id, arguments);
+        let history = game.history();
+        for _ in history.iter() {
+            game.undo();
+        }
+        let usual = registry::find(game_id)
+            .and_then(|entry| entry.build(arguments).ok())
+            .map(|start| start.position());
+        if usual.is_none_or(|usual| usual != game.position()) {
+            record.set_tag("Position", &game.position());
+        }
+        for mv in history.iter() {
+            record.moves.push(game.encode_move(*mv));
+            game.play(*mv);
+        }
+        if let Some(out) = game.outcome() {
+            record.set_tag("Result", result_tag(out, history.len()));
+        }
+        record
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(n, _)| n == name) {
+            Some(tag) => tag.1 = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string()))
+        }
+    }
+
+    pub fn game_id(&self) -> Result<&str, String> {
+        self.tag("Game").ok_or("The record does not name its game.".to_string())
+    }
+
+    pub fn arguments(&self) -> Result<Arguments, String> {
+        let mut arguments = Arguments::new();
+        for pair in self.tag("Parameters").unwrap_or("").split_whitespace() {
+            let (name, value) = pair
+                .split_once('=')
+                .ok_or(format!("Invalid parameter '{}'.", pair))?;
+            let value = value
+                .parse()
+                .map_err(|_| format!("Invalid parameter '{}'.", pair))?;
+            arguments.insert(name.to_string(), value);
+        }
+        Ok(arguments)
+    }
+
+    // Creates a new session of the recorded game and plays the recorded
+    // moves on it, checking that every one of them is legal.
+    pub fn replay(&self) -> Result<Box<dyn Game>, String> {
+        let id = self.game_id()?;
+        let entry = registry::find(id).ok_or(format!("Unknown game '{}'.", id))?;
+        let mut game = match self.tag("Position") {
+            Some(position) => entry.setup(&self.arguments()?, position)?,
+            None => entry.build(&self.arguments()?)?
+        };
+        for (ply, text) in self.moves.iter().enumerate() {
+            if game.outcome().is_some() {
+                return Err(format!("Move {} ('{}') comes after the game is over.", ply + 1, text))
+            }
+            match game.decode_move(text) {
+                Some(mv) => game.play(mv),
+                None => return Err(format!("Move {} ('{}') is not legal.", ply + 1, text))
+            }
+        }
+        Ok(game)
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut record = Record { tags: Vec::new(), moves: Vec::new() };
+        let mut lines = text.lines().map(|l| l.trim()).peekable();
+        while let Some(line) = lines.next_if(|l| l.is_empty() || l.starts_with('[')) {
+            if line.is_empty() {
+                continue
+            }
+            let (name, value) = parse_tag(line)
+                .ok_or(format!("Invalid tag line: '{}'", line))?;
+            record.set_tag(&name, &value);
+        }
+        for token in lines.flat_map(|l| l.split_whitespace()) {
+            let numbered = token
+                .strip_suffix('.')
+                .is_some_and(|n| n.chars().all(|c| c.is_ascii_digit()));
+            let result = ["1-0", "0-1", "1/2-1/2", "*"].contains(&token);
+            if !numbered && !result {
+                record.moves.push(token.to_string());
+            }
+        }
+        Ok(record)
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Could not read {}: {}", path, e))?;
+        Record::parse(&text)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        fs::write(path, self.to_string())
+            .map_err(|e| format!("Could not write {}: {}", path, e))
+    }
+}
+
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (name, value) in self.tags.iter() {
+            let escaped = value.replace('\\', "\\\\").replace('"', "\\\"");
+            writeln!(f, "[{} \"{}\"]", name, escaped)?;
+        }
+        writeln!(f)?;
+        let mut tokens = Vec::new();
+        for (ply, mv) in self.moves.iter().enumerate() {
+            if ply % 2 == 0 {
+                tokens.push(format!("{}.", ply / 2 + 1));
+            }
+            tokens.push(mv.clone());
+        }
+        tokens.push(self.tag("Result").unwrap_or("*").to_string());
+        writeln!(f, "{}", tokens.join(" "))
+    }
+}
+
+
+// Result tag for a finished match, given the terminal outcome for the
+// player to move and the number of moves that were played.
+pub fn result_tag(out: Outcome, plies: usize) -> &'static str {
+    let first_to_move = plies.is_multiple_of(2);
+    match (out, first_to_move) {
+        (Outcome::Tie(_), _) => "1/2-1/2",
+        (Outcome::Win(_), true) | (Outcome::Loss(_), false) => "1-0",
+        _ => "0-1"
+    }
+}
+
+
+/* HELPER FUNCTIONS */
+
+fn format_arguments(arguments: &Arguments) -> String {
+    let mut pairs: Vec<_> = arguments.iter().collect();
+    pairs.sort();
+    let pairs: Vec<String> = pairs
+        .into_iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect();
+    pairs.join(" ")
+}
+
+// Parses a line like [Name "value"], undoing escapes in the value.
+fn parse_tag(line: &str) -> Option<(String, String)> {
+    let inner = line.strip_prefix('[')?.strip_suffix(']')?;
+    let (name, quoted) = inner.split_once(' ')?;
+    let quoted = quoted.trim().strip_prefix('"')?.strip_suffix('"')?;
+    let mut value = String::new();
+    let mut chars = quoted.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            value.push(chars.next()?);
+        } else {
+            value.push(c);
+        }
+    }
+    Some((name.to_string(), value))
+}
+
+// Today's date in UTC as YYYY.MM.DD, from the days since the Unix epoch
+// (using Howard Hinnant's civil_from_days algorithm).
+fn today() -> String {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    let z = seconds / 86400 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}.{:02}.{:02}", year, month, day)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn arguments(pairs: &[(&str, i32)]) -> Arguments {
+        pairs.iter().map(|(name, value)| (name.to_string(), *value)).collect()
+    }
+
+    // Writes a session's record out, reads it back and replays it.
+    fn round_trip(id: &str, arguments: &Arguments, game: &mut dyn Game) -> (Record, Record, Box<dyn Game>) {
+        let record = Record::from_session(id, arguments, game);
+        let parsed = Record::parse(&record.to_string()).unwrap();
+        let replayed = parsed.replay().unwrap();
+        (record, parsed, replayed)
+    }
+
+    #[test]
+    fn unfinished_matches_from_a_position_round_trip() {
+        let args = arguments(&[("height", 3), ("width", 3), ("win", 3)]);
+        let mut game = registry::find("tic-tac-toe").unwrap().setup(&args, "O.X/.O./... X").unwrap();
+        for text in ["c3", "a3"] {
+            let mv = game.decode_move(text).unwrap();
+            game.play(mv);
+        }
+        let (record, parsed, replayed) = round_trip("tic-tac-toe", &args, game.as_mut());
+        let text = record.to_string();
+        assert!(text.contains("[Position \"O.X/.O./... X\"]\n"));
+        assert!(text.ends_with("\n1. c3 a3 *\n"));
+        assert_eq!(parsed.tags, record.tags);
+        assert_eq!(parsed.moves, vec!["c3", "a3"]);
+        assert_eq!(parsed.tag("Result"), Some("*"));
+        assert_eq!(replayed.position(), game.position());
+        assert!(replayed.outcome().is_none());
+    }
+
+    #[test]
+    fn finished_matches_round_trip() {
+        let args = arguments(&[("coins", 4)]);
+        let mut game = registry::find("zero-by-1-2").unwrap().build(&args).unwrap();
+        for text in ["1", "2", "1"] {
+            let mv = game.decode_move(text).unwrap();
+            game.play(mv);
+        }
+        let (mut record, parsed, replayed) = round_trip("zero-by-1-2", &args, game.as_mut());
+        assert_eq!(record.tag("Position"), None);
+        assert_eq!(parsed.tag("Result"), Some("1-0"));
+        assert_eq!(parsed.tag("Parameters"), Some("coins=4"));
+        assert!(replayed.outcome() == game.outcome());
+
+        record.set_tag("First", "A \"quoted\" name \\ with a backslash");
+        let parsed = Record::parse(&record.to_string()).unwrap();
+        assert_eq!(parsed.tag("First"), Some("A \"quoted\" name \\ with a backslash"));
+
+        record.moves.push("1".to_string());
+        assert!(record.replay().is_err());
+    }
+}
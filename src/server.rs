@@ -0,0 +1,502 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::time::Duration;
+use crate::game::*;
+use crate::game::registry::{self, Arguments};
+use crate::database::{table_key, Database};
+use crate::json::{self, Json};
+
+
+// JSON schemas for the body of every request and response, by endpoint.
+pub const SCHEMAS: &str = include_str!("server/schemas.json");
+
+// Largest request body the server is willing to read
+const MAX_BODY: usize = 1 << 20;
+
+// Requests are handled one at a time, so a client which stops sending holds
+// up every other one. It gets this long before the server gives up on it.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+
+// A game being played through the server, along with its solved table.
+struct Session {
+    game_id: String,
+    game: Box<dyn Game>,
+    table: Rc<HashMap<i32, Outcome>>,
+    plies: usize
+}
+
+type Response = (u16, Json);
+
+
+// Serves requests one at a time on the local machine. Solved tables are
+// shared between sessions of the same game, and come from the databases
+// given here when possible (otherwise the game is solved when first used).
+pub struct Server {
+    sessions: HashMap<u64, Session>,
+    tables: HashMap<String, Rc<HashMap<i32, Outcome>>>,
+    next_session: u64
+}
+
+impl Server {
+    pub fn new(databases: Vec<Database>) -> Self {
+        let tables = databases
+            .into_iter()
+            .map(|db| (db.key(), Rc::new(db.table)))
+            .collect();
+        Server {
+            sessions: HashMap::new(),
+            tables,
+            next_session: 1
+        }
+    }
+
+    pub fn listen(&mut self, port: u16) -> Result<(), String> {
+        let listener = TcpListener::bind(("127.0.0.1", port))
+            .map_err(|e| format!("Could not listen on port {}: {}", port, e))?;
+        println!("Listening on http://127.0.0.1:{}", port);
+        for stream in listener.incoming().flatten() {
+            self.respond(stream);
+        }
+        Ok(())
+    }
+
+    // Handles a single request, given its method, path and body.
+    pub fn handle(&mut self, method: &str, path: &str, body: &str) -> Response {
+        let segments: Vec<&str> = path
+            .split('?')
+            .next()
+            .unwrap_or("")
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .collect();
+        let body = if body.trim().is_empty() {
+            Json::Object(vec![])
+        } else {
+            match json::parse(body) {
+                Ok(body) => body,
+                Err(message) => return error(400, &format!("Invalid JSON: {}", message))
+            }
+        };
+        let result = match (method, segments.as_slice()) {
+            ("GET", ["schemas"]) => json::parse(SCHEMAS).map(|s| (200, s)).map_err(|m| (500, m)),
+            ("GET", ["games"]) => Ok((200, list_games())),
+            ("POST", ["sessions"]) => self.start_session(&body),
+            ("GET", ["sessions", id]) => self.with_session(id, |s| Ok(session_view(s))),
+            ("DELETE", ["sessions", id]) => self.end_session(id),
+            ("GET", ["sessions", id, "moves"]) => self.with_session(id, |s| {
+                Ok(Json::object(vec![("moves", move_list(s))]))
+            }),
+            ("POST", ["sessions", id, "moves"]) => self.with_session(id, |s| {
+                let text = body
+                    .get("move")
+                    .and_then(|m| m.as_str())
+                    .ok_or((400, "Expected a move.".to_string()))?;
+                if s.game.outcome().is_some() {
+                    return Err((409, "The game is over.".to_string()))
+                }
+                match s.game.decode_move(text) {
+                    Some(mv) => s.game.play(mv),
+                    None => return Err((400, format!("'{}' is not a legal move.", text)))
+                }
+                s.plies += 1;
+                Ok(session_view(s))
+            }),
+            ("POST", ["sessions", id, "undo"]) => self.with_session(id, |s| {
+                if s.plies == 0 {
+                    return Err((400, "There is no move to undo.".to_string()))
+                }
+                s.game.undo();
+                s.plies -= 1;
+                Ok(session_view(s))
+            }),
+            ("POST", ["query"]) => self.query(&body),
+            _ => Err((404, format!("No endpoint for {} {}.", method, path)))
+        };
+        match result {
+            Ok((status, body)) => (status, body),
+            Err((status, message)) => error(status, &message)
+        }
+    }
+
+    fn start_session(&mut self, body: &Json) -> Result<Response, (u16, String)> {
+        let (game_id, arguments) = game_request(body)?;
+        let entry = registry::find(&game_id)
+            .ok_or((404, format!("Unknown game '{}'.", game_id)))?;
+        let arguments = entry.complete(&arguments).map_err(|m| (400, m))?;
+        let mut game = start_game(&entry, &arguments, body)?;
+        let table = self.table_from(&game_id, &arguments, game.as_mut())?;
+        let id = self.next_session;
+        self.next_session += 1;
+        let mut session = Session { game_id, game, table, plies: 0 };
+        let mut view = session_view(&mut session);
+        if let Json::Object(fields) = &mut view {
+            fields.insert(0, ("session".to_string(), Json::Number(id as f64)));
+        }
+        self.sessions.insert(id, session);
+        Ok((201, view))
+    }
+
+    fn end_session(&mut self, id: &str) -> Result<Response, (u16, String)> {
+        let id = session_id(id)?;
+        match self.sessions.remove(&id) {
+            Some(_) => Ok((200, Json::object(vec![("session", Json::Number(id as f64))]))),
+            None => Err((404, format!("No session {}.", id)))
+        }
+    }
+
+    fn with_session(
+        &mut self,
+        id: &str,
+        action: impl FnOnce(&mut Session) -> Result<Json, (u16, String)>
+    ) -> Result<Response, (u16, String)> {
+        let id = session_id(id)?;
+        let session = self.sessions
+            .get_mut(&id)
+            .ok_or((404, format!("No session {}.", id)))?;
+        action(session).map(|body| (200, body))
+    }
+
+    // Looks a position up either by its state or by the moves leading to it
+    // from the start of the game (or from a given position).
+    fn query(&mut self, body: &Json) -> Result<Response, (u16, String)> {
+        let (game_id, arguments) = game_request(body)?;
+        let entry = registry::find(&game_id)
+            .ok_or((404, format!("Unknown game '{}'.", game_id)))?;
+        let arguments = entry.complete(&arguments).map_err(|m| (400, m))?;
+        let mut game = start_game(&entry, &arguments, body)?;
+        let state = match body.get("state") {
+            Some(state) => state
+                .as_i64()
+                .and_then(|state| i32::try_from(state).ok())
+                .ok_or((400, "Expected an integer state.".to_string()))?,
+            None => {
+                let moves = body.get("moves").and_then(|m| m.as_array()).cloned().unwrap_or_default();
+                for text in moves.iter() {
+                    let text = text.as_str().ok_or((400, "Expected moves as strings.".to_string()))?;
+                    if game.outcome().is_some() {
+                        return Err((409, "The game is over.".to_string()))
+                    }
+                    match game.decode_move(text) {
+                        Some(mv) => game.play(mv),
+                        None => return Err((400, format!("'{}' is not a legal move.", text)))
+                    }
+                }
+                game.state()
+            }
+        };
+        let table = match body.get("state") {
+            Some(_) => self.table(&game_id, &arguments)?,
+            None => self.table_from(&game_id, &arguments, game.as_mut())?
+        };
+        match table.get(&state) {
+            Some(out) => Ok((200, Json::object(vec![
+                ("game", Json::string(&game_id)),
+                ("state", Json::Number(state as f64)),
+                ("outcome", outcome_json(*out))
+            ]))),
+            None => Err((404, format!("State {} is not in the solved table.", state)))
+        }
+    }
+
+    // Positions which were set up rather than played from the start might not
+    // be in the game's table, in which case they get a table of their own.
+    fn table_from(
+        &mut self,
+        game_id: &str,
+        arguments: &Arguments,
+        game: &mut dyn Game
+    ) -> Result<Rc<HashMap<i32, Outcome>>, (u16, String)> {
+        let table = self.table(game_id, arguments)?;
+        if game.outcome().is_some() || table.contains_key(&game.state()) {
+            return Ok(table)
+        }
+        let mut seen: HashMap<i32, Outcome> = HashMap::new();
+        let out = solve(game, &mut seen);
+        seen.insert(game.state(), out);
+        Ok(Rc::new(seen))
+    }
+
+    // Solved tables are found by game and parameters, and solved if missing.
+    fn table(&mut self, game_id: &str, arguments: &Arguments) -> Result<Rc<HashMap<i32, Outcome>>, (u16, String)> {
+        let key = table_key(game_id, arguments);
+        if let Some(table) = self.tables.get(&key) {
+            return Ok(Rc::clone(table))
+        }
+        let mut game = registry::find(game_id)
+            .ok_or((404, format!("Unknown game '{}'.", game_id)))?
+            .build(arguments)
+            .map_err(|m| (400, m))?;
+        let mut seen: HashMap<i32, Outcome> = HashMap::new();
+        let out = solve(game.as_mut(), &mut seen);
+        seen.insert(game.state(), out);
+        let table = Rc::new(seen);
+        self.tables.insert(key, Rc::clone(&table));
+        Ok(table)
+    }
+
+    fn respond(&mut self, mut stream: TcpStream) {
+        stream.set_read_timeout(Some(READ_TIMEOUT)).ok();
+        let (status, body) = match read_request(&stream) {
+            Some((method, _, _)) if method == "OPTIONS" => (204, Json::Null),
+            Some((method, path, body)) => self.handle(&method, &path, &body),
+            None => error(400, "Malformed request.")
+        };
+        let text = if status == 204 { String::new() } else { body.to_string() };
+        let response = format!(
+            "HTTP/1.1 {} {}\r\n\
+            Content-Type: application/json\r\n\
+            Content-Length: {}\r\n\
+            Access-Control-Allow-Origin: *\r\n\
+            Access-Control-Allow-Methods: GET, POST, DELETE, OPTIONS\r\n\
+            Access-Control-Allow-Headers: Content-Type\r\n\
+            Connection: close\r\n\r\n{}",
+            status, reason(status), text.len(), text
+        );
+        stream.write_all(response.as_bytes()).ok();
+    }
+}
+
+
+/* HELPER FUNCTIONS */
+
+fn read_request(stream: &TcpStream) -> Option<(String, String, String)> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line).ok()?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let path = parts.next()?.to_string();
+    let mut length = 0;
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header).ok()?;
+        let header = header.trim();
+        if header.is_empty() {
+            break
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse().ok()?;
+            }
+        }
+    }
+    if length > MAX_BODY {
+        return None
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).ok()?;
+    Some((method, path, String::from_utf8(body).ok()?))
+}
+
+fn game_request(body: &Json) -> Result<(String, Arguments), (u16, String)> {
+    let game_id = body
+        .get("game")
+        .and_then(|g| g.as_str())
+        .ok_or((400, "Expected a game.".to_string()))?
+        .to_string();
+    let mut arguments = Arguments::new();
+    if let Some(fields) = body.get("arguments").and_then(|a| a.as_object()) {
+        for (name, value) in fields {
+            let value = value
+                .as_i64()
+                .ok_or((400, format!("Expected an integer for {}.", name)))?;
+            let value = i32::try_from(value)
+                .map_err(|_| (400, format!("{} is out of range.", name)))?;
+            arguments.insert(name.clone(), value);
+        }
+    }
+    Ok((game_id, arguments))
+}
+
+// Sessions start from the usual position, unless the body names another.
+fn start_game(
+    entry: &registry::Entry,
+    arguments: &Arguments,
+    body: &Json
+) -> Result<Box<dyn Game>, (u16, String)> {
+    let game = match body.get("position") {
+        Some(position) => {
+            let position = position
+                .as_str()
+                .ok_or((400, "Expected the position as a string.".to_string()))?;
+            entry.setup(arguments, position)
+        },
+        None => entry.build(arguments)
+    };
+    game.map_err(|m| (400, m))
+}
+
+fn session_id(text: &str) -> Result<u64, (u16, String)> {
+    text.parse().map_err(|_| (400, format!("Invalid session '{}'.", text)))
+}
+
+fn list_games() -> Json {
+    let games = registry::games()
+        .into_iter()
+        .map(|entry| {
+            let parameters = entry.parameters
+                .iter()
+                .map(|p| Json::object(vec![
+                    ("name", Json::string(p.name)),
+                    ("description", Json::string(p.description)),
+                    ("default", Json::Number(p.default as f64)),
+                    ("min", Json::Number(p.min as f64)),
+                    ("max", Json::Number(p.max as f64))
+                ]))
+                .collect();
+            Json::object(vec![
+                ("id", Json::string(entry.id)),
+                ("name", Json::string(entry.name)),
+                ("description", Json::string(entry.description)),
+                ("parameters", Json::Array(parameters))
+            ])
+        })
+        .collect();
+    Json::object(vec![("games", Json::Array(games))])
+}
+
+// Everything a front-end needs to show a session after each request.
+fn session_view(session: &mut Session) -> Json {
+    let moves = move_list(session);
+    let game = session.game.as_ref();
+    let value = match game.outcome() {
+        Some(out) => Some(out),
+        None => session.table.get(&game.state()).copied()
+    };
+    Json::object(vec![
+        ("game", Json::string(&session.game_id)),
+        ("state", Json::Number(game.state() as f64)),
+        ("position", Json::string(&game.position())),
+        ("board", Json::string(&game.render())),
+        ("finished", Json::Bool(game.outcome().is_some())),
+        ("outcome", value.map(outcome_json).unwrap_or(Json::Null)),
+        ("moves", moves)
+    ])
+}
+
+fn move_list(session: &mut Session) -> Json {
+    let moves = move_values(session.game.as_mut(), &session.table)
+        .into_iter()
+        .map(|(mv, out)| {
+            let mut fields = vec![("move".to_string(), Json::string(&session.game.encode_move(mv)))];
+            if let Json::Object(rest) = outcome_json(out) {
+                fields.extend(rest);
+            }
+            Json::Object(fields)
+        })
+        .collect();
+    Json::Array(moves)
+}
+
+fn outcome_json(out: Outcome) -> Json {
+    let (result, rem) = match out {
+        Outcome::Win(rem) => ("win", rem),
+        Outcome::Loss(rem) => ("loss", rem),
+        Outcome::Tie(rem) => ("tie", rem)
+    };
+    Json::object(vec![
+        ("result", Json::string(result)),
+        ("remoteness", Json::Number(rem as f64))
+    ])
+}
+
+fn error(status: u16, message: &str) -> Response {
+    (status, Json::object(vec![("error", Json::string(message))]))
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        204 => "No Content",
+        400 => "Bad Request",
+        404 => "Not Found",
+        409 => "Conflict",
+        _ => "Internal Server Error"
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    fn field<'a>(body: &'a Json, key: &str) -> &'a Json {
+        body.get(key).unwrap_or_else(|| panic!("No {} in {}", key, body))
+    }
+
+    #[test]
+    fn sessions_play_until_the_game_is_over() {
+        let mut server = Server::new(vec![]);
+        let (status, body) = server.handle("POST", "/sessions", r#"{"game": "zero-by-1-2", "arguments": {"coins": 3}}"#);
+        assert_eq!(status, 201);
+        assert_eq!(field(&body, "session").as_i64(), Some(1));
+        assert_eq!(field(&body, "position").as_str(), Some("3"));
+        assert_eq!(field(field(&body, "outcome"), "result").as_str(), Some("loss"));
+        assert_eq!(field(&body, "moves").as_array().unwrap().len(), 2);
+
+        let (status, body) = server.handle("POST", "/sessions/1/moves", r#"{"move": "2"}"#);
+        assert_eq!(status, 200);
+        assert_eq!(field(&body, "position").as_str(), Some("1"));
+        let (status, body) = server.handle("POST", "/sessions/1/moves", r#"{"move": "1"}"#);
+        assert_eq!(status, 200);
+        assert_eq!(field(&body, "finished"), &Json::Bool(true));
+        assert_eq!(server.handle("POST", "/sessions/1/moves", r#"{"move": "1"}"#).0, 409);
+        assert_eq!(server.handle("POST", "/sessions/1/undo", "").0, 200);
+        assert_eq!(server.handle("DELETE", "/sessions/1", "").0, 200);
+        assert_eq!(server.handle("GET", "/sessions/1", "").0, 404);
+    }
+
+    #[test]
+    fn bad_requests_are_rejected() {
+        let mut server = Server::new(vec![]);
+        let too_many = r#"{"game": "zero-by-1-2", "arguments": {"coins": 4294967300}}"#;
+        assert_eq!(server.handle("POST", "/sessions", too_many).0, 400);
+        let fraction = r#"{"game": "zero-by-1-2", "arguments": {"coins": 2.5}}"#;
+        assert_eq!(server.handle("POST", "/sessions", fraction).0, 400);
+        let state = r#"{"game": "zero-by-1-2", "state": 4294967296}"#;
+        assert_eq!(server.handle("POST", "/query", state).0, 400);
+        assert_eq!(server.handle("POST", "/sessions", &"[".repeat(200_000)).0, 400);
+        assert_eq!(server.handle("POST", "/sessions", r#"{"game": "chess"}"#).0, 404);
+        assert_eq!(server.handle("GET", "/nowhere", "").0, 404);
+    }
+
+    #[test]
+    fn query_answers_by_moves_or_state() {
+        let mut server = Server::new(vec![]);
+        let (status, body) = server.handle("POST", "/query", r#"{"game": "zero-by-1-2", "arguments": {"coins": 5}, "moves": ["1"]}"#);
+        assert_eq!(status, 200);
+        let state = field(&body, "state").as_i64().unwrap();
+        assert_eq!(field(field(&body, "outcome"), "result").as_str(), Some("win"));
+        let request = format!(r#"{{"game": "zero-by-1-2", "arguments": {{"coins": 5}}, "state": {}}}"#, state);
+        let (status, again) = server.handle("POST", "/query", &request);
+        assert_eq!(status, 200);
+        assert_eq!(field(&again, "outcome"), field(&body, "outcome"));
+    }
+
+    // A whole request and response over a socket.
+    #[test]
+    fn responds_over_http() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let body = r#"{"game": "zero-by-1-2", "arguments": {"coins": 4}}"#;
+        write!(
+            client,
+            "POST /sessions HTTP/1.1\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(), body
+        ).unwrap();
+        let (stream, _) = listener.accept().unwrap();
+        Server::new(vec![]).respond(stream);
+        let mut response = String::new();
+        client.read_to_string(&mut response).unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        assert!(head.starts_with("HTTP/1.1 201 Created\r\n"));
+        assert!(head.contains(&format!("Content-Length: {}\r\n", body.len())));
+        let body = json::parse(body).unwrap();
+        assert_eq!(field(&body, "position").as_str(), Some("4"));
+    }
+}
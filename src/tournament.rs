@@ -0,0 +1,220 @@
+use crate::agent::{play_match, Agent};
+use crate::game::{Game, Outcome};
+
+
+// z value for 95% confidence intervals
+const Z: f64 = 1.96;
+
+
+// Results of one agent against another, from the first agent's perspective.
+#[derive(Default, Clone, Copy)]
+pub struct Tally {
+    pub wins: u32,
+    pub losses: u32,
+    pub ties: u32
+}
+
+impl Tally {
+    pub fn games(&self) -> u32 {
+        self.wins + self.losses + self.ties
+    }
+
+    // Points per game, counting a tie as half a win
+    pub fn score(&self) -> f64 {
+        if self.games() == 0 {
+            return 0.0
+        }
+        (self.wins as f64 + 0.5 * self.ties as f64) / self.games() as f64
+    }
+
+    // Wilson score interval around the score, which behaves sensibly even
+    // for scores of zero or one and for small numbers of games.
+    pub fn interval(&self) -> (f64, f64) {
+        let n = self.games() as f64;
+        if n == 0.0 {
+            return (0.0, 1.0)
+        }
+        let p = self.score();
+        let center = (p + Z * Z / (2.0 * n)) / (1.0 + Z * Z / n);
+        let margin = Z * (p * (1.0 - p) / n + Z * Z / (4.0 * n * n)).sqrt() / (1.0 + Z * Z / n);
+        ((center - margin).max(0.0), (center + margin).min(1.0))
+    }
+
+    fn record(&mut self, out: Outcome) {
+        match out {
+            Outcome::Win(_) => self.wins += 1,
+            Outcome::Loss(_) => self.losses += 1,
+            Outcome::Tie(_) => self.ties += 1
+        }
+    }
+
+    fn flipped(&self) -> Tally {
+        Tally { wins: self.losses, losses: self.wins, ties: self.ties }
+    }
+}
+
+
+// Tallies for every pair of agents, where results[i][j] is agent i against
+// agent j (and the diagonal is empty). Agents which share a name get a
+// number after it, so that every one of them can be told apart.
+pub struct Results {
+    pub names: Vec<String>,
+    pub results: Vec<Vec<Tally>>
+}
+
+impl Results {
+    pub fn total(&self, agent: usize) -> Tally {
+        let mut total = Tally::default();
+        for tally in self.results[agent].iter() {
+            total.wins += tally.wins;
+            total.losses += tally.losses;
+            total.ties += tally.ties;
+        }
+        total
+    }
+
+    pub fn print(&self) {
+        println!(
+            "{:<16}{:<16}{:>6}{:>6}{:>6}{:>8}   95% CI",
+            "Agent", "Opponent", "W", "L", "T", "Score"
+        );
+        println!("{}", "-".repeat(76));
+        for (i, name) in self.names.iter().enumerate() {
+            for (j, opponent) in self.names.iter().enumerate() {
+                if i != j {
+                    print_row(name, opponent, &self.results[i][j]);
+                }
+            }
+        }
+        println!("{}", "-".repeat(76));
+        for (i, name) in self.names.iter().enumerate() {
+            print_row(name, "(all)", &self.total(i));
+        }
+    }
+}
+
+
+// Plays every pair of agents against each other the given number of times
+// from the game's current position, alternating which of them moves first.
+pub fn round_robin(game: &mut dyn Game, agents: &mut [Box<dyn Agent>], rounds: u32) -> Results {
+    let n = agents.len();
+    let mut pairs = Vec::new();
+    for i in 0..n {
+        for j in (i + 1)..n {
+            let (left, right) = agents.split_at_mut(j);
+            let (a, b) = (left[i].as_mut(), right[0].as_mut());
+            let mut tally = Tally::default();
+            for round in 0..rounds {
+                if round % 2 == 0 {
+                    tally.record(play_match(game, a, b).1);
+                } else {
+                    tally.record(flip(play_match(game, b, a).1));
+                }
+            }
+            pairs.push((i, j, tally));
+        }
+    }
+    let mut results = vec![vec![Tally::default(); n]; n];
+    for (i, j, tally) in pairs {
+        results[i][j] = tally;
+        results[j][i] = tally.flipped();
+    }
+    Results {
+        names: distinct_names(agents),
+        results
+    }
+}
+
+
+/* HELPER FUNCTIONS */
+
+fn print_row(name: &str, opponent: &str, tally: &Tally) {
+    let (low, high) = tally.interval();
+    println!(
+        "{:<16}{:<16}{:>6}{:>6}{:>6}{:>8.3}   [{:.3}, {:.3}]",
+        name, opponent, tally.wins, tally.losses, tally.ties, tally.score(), low, high
+    );
+}
+
+// Names agents as they name themselves, numbering repeated names after the
+// first, as in "Random", "Random #2".
+fn distinct_names(agents: &[Box<dyn Agent>]) -> Vec<String> {
+    let names: Vec<String> = agents.iter().map(|a| a.name()).collect();
+    names
+        .iter()
+        .enumerate()
+        .map(|(i, name)| match names[..i].iter().filter(|n| *n == name).count() {
+            0 => name.clone(),
+            k => format!("{} #{}", name, k + 1)
+        })
+        .collect()
+}
+
+fn flip(out: Outcome) -> Outcome {
+    match out {
+        Outcome::Win(rem) => Outcome::Loss(rem),
+        Outcome::Loss(rem) => Outcome::Win(rem),
+        tie => tie
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::from_spec;
+    use crate::game::{solve, tic_tac_toe, zero_by_1_2};
+    use std::collections::HashMap;
+
+    fn assert_interval(tally: Tally, low: f64, high: f64) {
+        let (l, h) = tally.interval();
+        assert!((l - low).abs() < 5e-5 && (h - high).abs() < 5e-5, "[{}, {}]", l, h);
+    }
+
+    #[test]
+    fn wilson_intervals() {
+        assert_interval(Tally { wins: 8, losses: 2, ties: 0 }, 0.4902, 0.9433);
+        assert_interval(Tally { wins: 0, losses: 10, ties: 0 }, 0.0, 0.2775);
+        assert_interval(Tally { wins: 0, losses: 1, ties: 0 }, 0.0, 0.7935);
+        assert_interval(Tally { wins: 50, losses: 50, ties: 0 }, 0.4038, 0.5962);
+        assert_interval(Tally::default(), 0.0, 1.0);
+    }
+
+    // Tic-tac-toe is a tie, so the solver never loses it from either side.
+    #[test]
+    fn perfect_never_loses_to_random() {
+        let mut game = tic_tac_toe::Session::new(3, 3, 3);
+        let mut agents = vec![from_spec("perfect", 0).unwrap(), from_spec("random", 1).unwrap()];
+        let results = round_robin(&mut game, &mut agents, 20);
+        assert_eq!(results.total(0).losses, 0);
+        assert_eq!(results.total(0).games(), 20);
+    }
+
+    // Elsewhere, it still gets at least what the position is worth to it.
+    #[test]
+    fn perfect_gets_at_least_the_solved_value() {
+        for coins in 0..12 {
+            let mut game = zero_by_1_2::Session::new(coins);
+            let value = match game.outcome() {
+                Some(out) => out,
+                None => solve(&mut game, &mut HashMap::new())
+            };
+            for seed in 0..5 {
+                let mut perfect = from_spec("perfect", 0).unwrap();
+                let mut random = from_spec("random", seed).unwrap();
+                let (_, first, _) = play_match(&mut game, perfect.as_mut(), random.as_mut());
+                let (_, second, _) = play_match(&mut game, random.as_mut(), perfect.as_mut());
+                assert!(rank(first) >= rank(value), "{} coins", coins);
+                assert!(rank(flip(second)) >= rank(flip(value)), "{} coins", coins);
+            }
+        }
+    }
+
+    fn rank(out: Outcome) -> i32 {
+        match out {
+            Outcome::Loss(_) => 0,
+            Outcome::Tie(_) => 1,
+            Outcome::Win(_) => 2
+        }
+    }
+}
@@ -0,0 +1,275 @@
+use std::io::{self, Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, TryRecvError};
+use std::sync::Arc;
+use std::collections::HashMap;
+use std::{thread, time::Duration};
+use crate::game::*;
+use crate::game::registry::{self, Arguments};
+use crate::ui::describe;
+use uuid::Uuid;
+
+
+/* ANSI ESCAPE SEQUENCES */
+const CLEAR: &str = "\x1b[2J\x1b[H";
+const HIDE_CURSOR: &str = "\x1b[?25l";
+const SHOW_CURSOR: &str = "\x1b[?25h";
+const REVERSE: &str = "\x1b[7m";
+const BOLD: &str = "\x1b[1m";
+const GREEN: &str = "\x1b[32m";
+const RED: &str = "\x1b[31m";
+const YELLOW: &str = "\x1b[33m";
+const RESET: &str = "\x1b[0m";
+
+// Spaces between the board and the panel of moves
+const PANEL_GAP: usize = 6;
+
+
+enum Key {
+    Up,
+    Down,
+    Select,
+    Undo,
+    Swap,
+    Quit,
+    Other
+}
+
+// What the screen shows besides the board and the moves.
+struct View<'a> {
+    title: &'a str,
+    values: &'a [(Uuid, Outcome)],
+    cursor: usize,
+    status: String
+}
+
+
+// Full-screen terminal interface for playing against the solver. The game
+// is solved on a background thread while the screen shows its progress, and
+// the moves are then picked from a list annotated with their values. Games
+// start from the given position, if any, instead of the usual one.
+pub fn run(id: &str, arguments: &Arguments, position: Option<&str>) -> Result<(), String> {
+    let entry = match registry::find(id) {
+        Some(entry) => entry,
+        None => return Err(format!("Unknown game '{}'. Try list-games.", id))
+    };
+    let mut game = start(id, arguments, position)?;
+    let _raw = RawMode::enter()?;
+    let seen = solve_in_background(id, arguments, position, entry.name)?;
+    play(game.as_mut(), &seen, entry.name);
+    Ok(())
+}
+
+
+/* HELPER FUNCTIONS */
+
+// Keeps the terminal in raw mode for as long as it lives, and puts back the
+// previous settings when dropped, even if the program is panicking.
+struct RawMode {
+    saved: String
+}
+
+impl RawMode {
+    fn enter() -> Result<Self, String> {
+        let saved = stty(&["-g"])
+            .ok_or("The terminal UI needs an interactive terminal.")?;
+        stty(&["raw", "-echo"])
+            .ok_or("Could not put the terminal in raw mode.")?;
+        print!("{}", HIDE_CURSOR);
+        Ok(RawMode { saved: saved.trim().to_string() })
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        print!("{}{}", SHOW_CURSOR, CLEAR);
+        io::stdout().flush().ok();
+        stty(&[&self.saved]);
+    }
+}
+
+fn start(id: &str, arguments: &Arguments, position: Option<&str>) -> Result<Box<dyn Game>, String> {
+    let entry = registry::find(id).ok_or(format!("Unknown game '{}'.", id))?;
+    match position {
+        Some(position) => entry.setup(arguments, position),
+        None => entry.build(arguments)
+    }
+}
+
+// Sessions cannot be sent across threads, so the solver thread builds its
+// own from the registry. Tables are keyed by state, so they work for both.
+fn solve_in_background(
+    id: &str,
+    arguments: &Arguments,
+    position: Option<&str>,
+    title: &str
+) -> Result<HashMap<i32, Outcome>, String> {
+    let counter = Arc::new(AtomicUsize::new(0));
+    let (sender, receiver) = mpsc::channel();
+    let (id, arguments) = (id.to_string(), arguments.clone());
+    let position = position.map(|p| p.to_string());
+    let shared = Arc::clone(&counter);
+    thread::Builder::new()
+        .stack_size(256 * 1024 * 1024)
+        .spawn(move || {
+            let mut game = start(&id, &arguments, position.as_deref())
+                .expect("Could not build game.");
+            let mut seen: HashMap<i32, Outcome> = HashMap::new();
+            if game.outcome().is_none() {
+                let out = solve_with_progress(game.as_mut(), &mut seen, &mut |count| {
+                    shared.store(count, Ordering::Relaxed);
+                });
+                seen.insert(game.state(), out);
+            }
+            sender.send(seen).ok();
+        })
+        .map_err(|e| format!("Could not start the solver: {}", e))?;
+    let spinner = ['|', '/', '-', '\\'];
+    let mut frame = 0;
+    loop {
+        // The solver thread only hangs up without sending if it panicked
+        match receiver.try_recv() {
+            Ok(seen) => return Ok(seen),
+            Err(TryRecvError::Disconnected) => return Err("The solver stopped unexpectedly.".to_string()),
+            Err(TryRecvError::Empty) => ()
+        }
+        draw(&[
+            format!("{}{}{}", BOLD, title, RESET),
+            String::new(),
+            format!(
+                "{} Solving... {} positions so far",
+                spinner[frame % spinner.len()],
+                counter.load(Ordering::Relaxed)
+            )
+        ]);
+        frame += 1;
+        thread::sleep(Duration::from_millis(100));
+    }
+}
+
+fn play(game: &mut dyn Game, seen: &HashMap<i32, Outcome>, title: &str) {
+    let mut human_first = true;
+    let mut ply = 0;
+    let mut cursor = 0;
+    loop {
+        let human_turn = (ply % 2 == 0) == human_first;
+        let values = if game.outcome().is_none() {
+            move_values(game, seen)
+        } else {
+            vec![]
+        };
+        if !human_turn && !values.is_empty() {
+            game.play(values[0].0);
+            ply += 1;
+            continue
+        }
+        cursor = cursor.min(values.len().saturating_sub(1));
+        // Outcomes are given from the perspective of the player to move
+        let status = match (game.outcome(), human_turn) {
+            (Some(Outcome::Tie(_)), _) => "It's a tie!".to_string(),
+            (Some(Outcome::Win(_)), true) | (Some(Outcome::Loss(_)), false) => "You win!".to_string(),
+            (Some(_), _) => "You lose!".to_string(),
+            (None, _) => format!("Your move. You have a {}.", describe(seen[&game.state()]))
+        };
+        let view = View { title, values: &values, cursor, status };
+        draw(&layout(game, &view));
+        match read_key() {
+            Key::Up => cursor = cursor.saturating_sub(1),
+            Key::Down => cursor += 1,
+            Key::Select if !values.is_empty() => {
+                game.play(values[cursor].0);
+                ply += 1;
+                cursor = 0;
+            },
+            Key::Undo if ply >= 2 => {
+                game.undo();
+                game.undo();
+                ply -= 2;
+            },
+            // The computer takes over the side to move
+            Key::Swap => human_first = !human_first,
+            Key::Quit => return,
+            _ => ()
+        }
+    }
+}
+
+// Puts the board on the left and the moves with their values on the right.
+fn layout(game: &dyn Game, view: &View) -> Vec<String> {
+    let board: Vec<String> = game.render().lines().map(|l| l.to_string()).collect();
+    let board_width = board.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+    let mut panel = vec![format!("{}Moves{}", BOLD, RESET)];
+    for (i, (mv, out)) in view.values.iter().enumerate() {
+        let color = match out {
+            Outcome::Win(_) => GREEN,
+            Outcome::Loss(_) => RED,
+            Outcome::Tie(_) => YELLOW
+        };
+        let marker = if i == view.cursor { REVERSE } else { "" };
+        panel.push(format!(
+            "{}{}{:>6}  {:<12}{}",
+            marker, color, game.encode_move(*mv), describe(*out), RESET
+        ));
+    }
+    let mut lines = vec![format!("{}{}{}", BOLD, view.title, RESET), String::new()];
+    for i in 0..board.len().max(panel.len()) {
+        let left = board.get(i).map(|l| l.as_str()).unwrap_or("");
+        let right = panel.get(i).map(|l| l.as_str()).unwrap_or("");
+        let padding = board_width - left.chars().count() + PANEL_GAP;
+        lines.push(format!("{}{}{}", left, " ".repeat(padding), right));
+    }
+    lines.push(String::new());
+    lines.push(view.status.clone());
+    lines.push(String::new());
+    lines.push("up/down or k/j: choose   enter: play   u: undo   s: swap sides   q: quit".to_string());
+    lines
+}
+
+// The terminal is in raw mode, so lines need explicit carriage returns.
+fn draw(lines: &[String]) {
+    let mut out = io::stdout();
+    write!(out, "{}{}", CLEAR, lines.join("\r\n")).ok();
+    out.flush().ok();
+}
+
+fn read_key() -> Key {
+    let mut stdin = io::stdin();
+    let mut byte = [0u8; 1];
+    if stdin.read(&mut byte).unwrap_or(0) == 0 {
+        return Key::Quit
+    }
+    match byte[0] {
+        b'k' => Key::Up,
+        b'j' => Key::Down,
+        b'\r' | b'\n' | b' ' => Key::Select,
+        b'u' => Key::Undo,
+        b's' => Key::Swap,
+        b'q' | 3 => Key::Quit,
+        // Arrow keys arrive as ESC [ A and ESC [ B
+        0x1b => {
+            let mut sequence = [0u8; 2];
+            if stdin.read_exact(&mut sequence).is_err() {
+                return Key::Quit
+            }
+            match sequence {
+                [b'[', b'A'] => Key::Up,
+                [b'[', b'B'] => Key::Down,
+                _ => Key::Other
+            }
+        },
+        _ => Key::Other
+    }
+}
+
+fn stty(args: &[&str]) -> Option<String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None
+    }
+    String::from_utf8(output.stdout).ok()
+}
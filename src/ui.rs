@@ -0,0 +1,234 @@
+use std::io::{self, Write};
+use std::collections::HashMap;
+use crate::game::*;
+use crate::game::registry::{self, Arguments};
+use crate::agent::Agent;
+use crate::agent::perfect::Perfect;
+use crate::agent::imperfect::{Difficulty, Imperfect};
+use crate::record::Record;
+use crate::analysis::value_history;
+use crate::chart;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+
+// Lets a human pick a game, a side and a difficulty, and then play it against
+// the solver in the terminal. At the perfect difficulty, the solver always
+// makes one of its best moves.
+pub fn play() {
+    println!("\n ----------- GAME SOLVER ----------- \n");
+    if let Some((id, arguments, game)) = choose_game() {
+        play_game(&id, &arguments, game);
+    }
+}
+
+
+// Plays a game which has already been set up, asking for a side and a
+// difficulty. The registry id and arguments are only used to record it.
+pub fn play_game(id: &str, arguments: &Arguments, mut game: Box<dyn Game>) {
+    let human_first = match prompt("Do you want to move first? [y/n]: ") {
+        Some(answer) => !answer.to_lowercase().starts_with('n'),
+        None => return
+    };
+    let difficulty = match prompt_difficulty() {
+        Some(difficulty) => difficulty,
+        None => return
+    };
+    println!("\nSolving...");
+    let mut seen: HashMap<i32, Outcome> = HashMap::new();
+    let out = solve(game.as_mut(), &mut seen);
+    seen.insert(game.state(), out);
+    let mut computer: Box<dyn Agent> = match difficulty {
+        Some(difficulty) => {
+            Box::new(Imperfect::with_table(difficulty.temperature(), clock_seed(), seen.clone()))
+        },
+        None => Box::new(Perfect::with_table(seen.clone()))
+    };
+    println!("Enter moves as shown on the board. Type 'hint' for the value of");
+    println!("every move, 'undo' to take back your last move, 'chart' to see how");
+    println!("the value of the match has changed, 'save <file>' to record the");
+    println!("match so far, or 'quit' to stop.\n");
+    let players = if human_first {
+        ("Human".to_string(), computer.name())
+    } else {
+        (computer.name(), "Human".to_string())
+    };
+    let mut ply = 0;
+    loop {
+        println!("{}\n", game.render());
+        let human_turn = (ply % 2 == 0) == human_first;
+        if let Some(out) = game.outcome() {
+            report(out, human_turn);
+            if let Some(path) = prompt("Save the match to a file? Enter its name, or nothing to skip: ") {
+                if !path.is_empty() {
+                    save_record(id, arguments, game.as_mut(), &players, &path);
+                }
+            }
+            return
+        }
+        if human_turn {
+            let text = match prompt("Your move: ") {
+                Some(text) => text,
+                None => return
+            };
+            match text.as_str() {
+                "quit" => return,
+                "hint" => print_hints(game.as_mut(), &seen),
+                "chart" => print_chart(game.as_mut(), &seen),
+                "undo" if ply >= 2 => {
+                    game.undo();
+                    game.undo();
+                    ply -= 2;
+                },
+                "undo" => println!("There is no move of yours to take back.\n"),
+                _ if text.starts_with("save ") => {
+                    save_record(id, arguments, game.as_mut(), &players, text[5..].trim());
+                },
+                _ => match game.decode_move(&text) {
+                    Some(mv) => {
+                        game.play(mv);
+                        ply += 1;
+                    },
+                    None => println!("'{}' is not a legal move here.\n", text)
+                }
+            }
+        } else {
+            let mv = match computer.choose_move(game.as_mut()) {
+                Ok(mv) => mv,
+                Err(message) => {
+                    println!("The computer forfeits: {}\n", message);
+                    return
+                }
+            };
+            println!("The computer plays {}.\n", game.encode_move(mv));
+            game.play(mv);
+            ply += 1;
+        }
+    }
+}
+
+
+/* HELPER FUNCTIONS */
+
+fn print_chart(game: &mut dyn Game, seen: &HashMap<i32, Outcome>) {
+    let moves: Vec<String> = game
+        .history()
+        .into_iter()
+        .map(|mv| game.encode_move(mv))
+        .collect();
+    println!("{}\n", chart::ascii(&value_history(game, seen), &moves));
+}
+
+fn choose_game() -> Option<(String, Arguments, Box<dyn Game>)> {
+    let games = registry::games();
+    println!("Available games:\n");
+    for (i, entry) in games.iter().enumerate() {
+        println!("  {}. {}", i + 1, entry.name);
+    }
+    println!();
+    loop {
+        let choice = prompt("Choose a game: ")?;
+        let entry = match choice.parse::<usize>() {
+            Ok(n) if n >= 1 && n <= games.len() => &games[n - 1],
+            _ => {
+                println!("Please pick one of the games above.");
+                continue
+            }
+        };
+        println!("\n{}\n", entry.description);
+        let mut arguments = Arguments::new();
+        for p in entry.parameters.iter() {
+            let value = prompt_number(p.description, p.default, p.min, p.max)?;
+            arguments.insert(p.name.to_string(), value);
+        }
+        println!();
+        match entry.build(&arguments) {
+            Ok(game) => return Some((entry.id.to_string(), arguments, game)),
+            Err(message) => println!("{}\n", message)
+        }
+    }
+}
+
+fn save_record(id: &str, arguments: &Arguments, game: &mut dyn Game, players: &(String, String), path: &str) {
+    let mut record = Record::from_session(id, arguments, game);
+    record.set_tag("First", &players.0);
+    record.set_tag("Second", &players.1);
+    match record.save(path) {
+        Ok(()) => println!("Saved the match to {}.\n", path),
+        Err(message) => println!("{}\n", message)
+    }
+}
+
+// Returns None once the input runs out, so that callers can stop cleanly.
+fn prompt(text: &str) -> Option<String> {
+    print!("{}", text);
+    io::stdout().flush().ok()?;
+    let mut line = String::new();
+    match io::stdin().read_line(&mut line) {
+        Ok(0) | Err(_) => {
+            println!();
+            None
+        },
+        Ok(_) => Some(line.trim().to_string())
+    }
+}
+
+// None stands for perfect play.
+fn prompt_difficulty() -> Option<Option<Difficulty>> {
+    loop {
+        let answer = prompt("Difficulty [easy/medium/hard/perfect, default perfect]: ")?;
+        if answer.is_empty() || answer == "perfect" {
+            return Some(None)
+        }
+        match Difficulty::parse(&answer) {
+            Some(difficulty) => return Some(Some(difficulty)),
+            None => println!("Please pick one of the difficulties above.")
+        }
+    }
+}
+
+// Seeds the computer's choices differently every time.
+fn clock_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+fn prompt_number(text: &str, default: i32, min: i32, max: i32) -> Option<i32> {
+    loop {
+        let answer = prompt(&format!("{} [{}-{}, default {}]: ", text, min, max, default))?;
+        if answer.is_empty() {
+            return Some(default)
+        }
+        match answer.parse::<i32>() {
+            Ok(n) if (min..=max).contains(&n) => return Some(n),
+            _ => println!("Please enter a number between {} and {}.", min, max)
+        }
+    }
+}
+
+fn print_hints(game: &mut dyn Game, seen: &HashMap<i32, Outcome>) {
+    for (mv, out) in move_values(game, seen) {
+        println!("  {:>6}: {}", game.encode_move(mv), describe(out));
+    }
+    println!();
+}
+
+// The outcome is given from the perspective of the player to move.
+fn report(out: Outcome, human_turn: bool) {
+    let message = match (out, human_turn) {
+        (Outcome::Tie(_), _) => "It's a tie!",
+        (Outcome::Win(_), true) | (Outcome::Loss(_), false) => "You win!",
+        (Outcome::Loss(_), true) | (Outcome::Win(_), false) => "You lose!"
+    };
+    println!("{}", message);
+}
+
+// Short description of an outcome, such as "win in 3".
+pub fn describe(out: Outcome) -> String {
+    match out {
+        Outcome::Win(rem) => format!("win in {}", rem),
+        Outcome::Loss(rem) => format!("loss in {}", rem),
+        Outcome::Tie(rem) => format!("tie in {}", rem)
+    }
+}